@@ -0,0 +1,64 @@
+use std::collections::{HashMap, HashSet};
+use std::hash::Hash;
+
+use crate::{ItemSet, SupportCounts, Transaction};
+
+type TidSet = HashSet<usize>;
+
+/// Eclat: mines the same frequent itemsets as `apriori` from a vertical
+/// item -> transaction-id layout, where the support of an itemset is the size
+/// of the intersection of its items' tidsets. Itemsets are sorted, so the
+/// result can be passed straight to `generate_rules`.
+pub fn eclat<I>(transactions: &[Transaction<I>], min_support: f64) -> (Vec<ItemSet<I>>, SupportCounts<I>)
+where
+    I: Eq + Hash + Ord + Clone,
+{
+    let min_count = (min_support * transactions.len() as f64).ceil() as usize;
+
+    let mut tidsets: HashMap<I, TidSet> = HashMap::new();
+    for (tid, transaction) in transactions.iter().enumerate() {
+        for item in transaction {
+            tidsets.entry(item.clone()).or_default().insert(tid);
+        }
+    }
+
+    let mut class: Vec<(I, TidSet)> = tidsets
+        .into_iter()
+        .filter(|(_, tids)| tids.len() >= min_count)
+        .collect();
+    class.sort_by(|a, b| a.0.cmp(&b.0));
+
+    let mut frequent_itemsets = Vec::new();
+    let mut support_counts = SupportCounts::new();
+    extend(&[], &class, min_count, &mut frequent_itemsets, &mut support_counts);
+
+    (frequent_itemsets, support_counts)
+}
+
+fn extend<I>(
+    prefix: &[I],
+    class: &[(I, TidSet)],
+    min_count: usize,
+    frequent_itemsets: &mut Vec<ItemSet<I>>,
+    support_counts: &mut SupportCounts<I>,
+) where
+    I: Eq + Hash + Ord + Clone,
+{
+    for (i, (item, tids)) in class.iter().enumerate() {
+        let mut itemset = prefix.to_vec();
+        itemset.push(item.clone());
+
+        frequent_itemsets.push(itemset.clone());
+        support_counts.insert(itemset.clone(), tids.len());
+
+        let suffix_class: Vec<(I, TidSet)> = class[i + 1..]
+            .iter()
+            .map(|(other, other_tids)| (other.clone(), tids.intersection(other_tids).cloned().collect::<TidSet>()))
+            .filter(|(_, joined)| joined.len() >= min_count)
+            .collect();
+
+        if !suffix_class.is_empty() {
+            extend(&itemset, &suffix_class, min_count, frequent_itemsets, support_counts);
+        }
+    }
+}
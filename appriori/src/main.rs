@@ -1,57 +1,78 @@
+mod eclat;
+
 use std::collections::{HashMap, HashSet};
+use std::hash::Hash;
+
+use common::cli::{self, Args};
+use common::{
+    dedup_transactions, evaluate_rules, filter_redundant, is_exact, minimal_antecedent_rules,
+    read_transactions_json, rules_to_csv, rules_to_json, top_rules, value_score, ItemConstraints,
+    ItemDictionary, RankBy, Rule,
+};
+use eclat::eclat;
 
-type ItemSet = Vec<char>;
-type Transaction = HashSet<char>;
+type ItemSet<I = char> = Vec<I>;
+/// A transaction is a set of items: repeated items collapse, matching
+/// `dedup_transactions` on the FP-Growth side.
+type Transaction<I = char> = HashSet<I>;
 type SupportCount = usize;
+type SupportCounts<I = char> = HashMap<ItemSet<I>, SupportCount>;
+
+/// Which antecedent/consequent splits `generate_rules` emits.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+enum RuleShape {
+    /// Every non-empty split of each frequent itemset.
+    #[default]
+    Any,
+    /// Only rules `X => {y}`; other antecedents are never enumerated.
+    SingleConsequent,
+}
+
+/// Joins frequent `(k-1)`-itemsets into `k`-candidates. Itemsets must be
+/// sorted; `p` and `q` join only when `p[..k-2] == q[..k-2]` and
+/// `p[k-2] < q[k-2]`, so every candidate is produced exactly once. Candidates
+/// with an infrequent `(k-1)`-subset are pruned. Prefix comparison and
+/// pruning work on item references, so only surviving candidates are cloned.
+fn generate_candidates<I>(l_prev: &[ItemSet<I>], k: usize) -> Vec<ItemSet<I>>
+where
+    I: Eq + Hash + Ord + Clone,
+{
+    debug_assert!(
+        l_prev.iter().all(|itemset| itemset.windows(2).all(|w| w[0] < w[1])),
+        "itemsets passed to generate_candidates must be sorted"
+    );
+
+    let mut sorted_prev: Vec<&ItemSet<I>> = l_prev.iter().filter(|p| p.len() == k - 1).collect();
+    sorted_prev.sort();
+    sorted_prev.dedup();
 
-fn generate_candidates(l_prev: &[ItemSet], k: usize) -> Vec<ItemSet> {
+    let frequent: HashSet<Vec<&I>> = sorted_prev.iter().map(|p| p.iter().collect()).collect();
     let mut result = Vec::new();
 
-    for (i, p) in l_prev.iter().enumerate() {
-        let p_set: HashSet<char> = p.iter().cloned().collect();
+    for (i, p) in sorted_prev.iter().enumerate() {
+        for q in sorted_prev.iter().skip(i + 1) {
+            if p[..k - 2] != q[..k - 2] {
+                break;
+            }
 
-        for q in l_prev.iter().skip(i + 1) {
-            let q_set: HashSet<char> = q.iter().cloned().collect();
+            if p[k - 2] >= q[k - 2] {
+                continue;
+            }
 
-            if p.len() >= k - 1 && q.len() >= k - 1 {
-                let mut can_join = true;
-                for i in 0..k - 2 {
-                    if p[i] != q[i] {
-                        can_join = false;
-                        break;
-                    }
-                }
+            let union: Vec<&I> = p.iter().chain(std::iter::once(&q[k - 2])).collect();
 
-                if can_join && p[k - 2] != q[k - 2] {
-                    let mut union = p_set.union(&q_set).cloned().collect::<Vec<char>>();
-                    union.sort();
-
-                    if union.len() == k {
-                        let mut is_valid = true;
-
-                        for i in 0..k {
-                            let mut subset = union.clone();
-                            subset.remove(i);
-
-                            let mut found = false;
-                            for existing in l_prev {
-                                if subset == *existing {
-                                    found = true;
-                                    break;
-                                }
-                            }
-
-                            if !found {
-                                is_valid = false;
-                                break;
-                            }
-                        }
-
-                        if is_valid {
-                            result.push(union);
-                        }
-                    }
-                }
+            let is_valid = (0..k).all(|skip| {
+                let subset: Vec<&I> = union
+                    .iter()
+                    .enumerate()
+                    .filter(|(j, _)| *j != skip)
+                    .map(|(_, item)| *item)
+                    .collect();
+                frequent.contains(&subset)
+            });
+
+            if is_valid {
+                result.push(union.into_iter().cloned().collect());
             }
         }
     }
@@ -59,18 +80,20 @@ fn generate_candidates(l_prev: &[ItemSet], k: usize) -> Vec<ItemSet> {
     result
 }
 
-fn calculate_support(
-    candidates: &[ItemSet],
-    transactions: &[Transaction],
-) -> HashMap<Vec<char>, SupportCount> {
+fn calculate_support<I>(
+    candidates: &[ItemSet<I>],
+    transactions: &[Transaction<I>],
+) -> SupportCounts<I>
+where
+    I: Eq + Hash + Ord + Clone,
+{
     let mut counts = HashMap::new();
 
     for c in candidates {
-        let c_set: HashSet<_> = c.iter().cloned().collect();
         let mut count = 0;
 
         for t in transactions {
-            if c_set.is_subset(t) {
+            if c.iter().all(|item| t.contains(item)) {
                 count += 1;
             }
         }
@@ -81,12 +104,15 @@ fn calculate_support(
     counts
 }
 
-fn get_frequent_itemsets(
-    candidates: &[ItemSet],
-    support_counts: &HashMap<Vec<char>, SupportCount>,
+fn get_frequent_itemsets<I>(
+    candidates: &[ItemSet<I>],
+    support_counts: &SupportCounts<I>,
     min_support: f64,
     transaction_count: usize,
-) -> Vec<ItemSet> {
+) -> Vec<ItemSet<I>>
+where
+    I: Eq + Hash + Ord + Clone,
+{
     let min_count = (min_support * transaction_count as f64).ceil() as usize;
 
     candidates
@@ -96,15 +122,41 @@ fn get_frequent_itemsets(
         .collect()
 }
 
-fn generate_all_subsets(itemset: &[char]) -> Vec<Vec<char>> {
+/// Transaction reduction between levels: keeps only items that occur in some
+/// frequent `k`-itemset and drops transactions left with fewer than `k + 1`
+/// items. Neither can contribute to a `(k+1)`-candidate, so supports of later
+/// candidates are unchanged.
+fn reduce_transactions<I>(
+    transactions: &[Transaction<I>],
+    frequent_k: &[ItemSet<I>],
+    k: usize,
+) -> Vec<Transaction<I>>
+where
+    I: Eq + Hash + Ord + Clone,
+{
+    let live_items: HashSet<&I> = frequent_k.iter().flatten().collect();
+
+    transactions
+        .iter()
+        .map(|t| {
+            t.iter()
+                .filter(|item| live_items.contains(item))
+                .cloned()
+                .collect::<Transaction<I>>()
+        })
+        .filter(|t| t.len() > k)
+        .collect()
+}
+
+fn generate_all_subsets<I: Clone>(itemset: &[I]) -> Vec<Vec<I>> {
     let n = itemset.len();
     let mut result = Vec::new();
 
     for mask in 1..(1 << n) - 1 {
         let mut subset = Vec::new();
-        for i in 0..n {
+        for (i, item) in itemset.iter().enumerate() {
             if (mask >> i) & 1 == 1 {
-                subset.push(itemset[i]);
+                subset.push(item.clone());
             }
         }
         result.push(subset);
@@ -113,12 +165,27 @@ fn generate_all_subsets(itemset: &[char]) -> Vec<Vec<char>> {
     result
 }
 
-fn generate_rules(
-    frequent_itemsets: &[ItemSet],
-    support_counts: &HashMap<Vec<char>, SupportCount>,
+/// Emits rules meeting both `min_confidence` and `min_lift`. A `min_lift` of
+/// `1.0` keeps only rules whose items are at least independent; `0.0` keeps
+/// every confident rule. Rules rejected by `constraints` are skipped before
+/// any measure is computed. Each rule's `value_score` is taken from
+/// `item_values`, or 0 when it is `None`.
+#[allow(clippy::too_many_arguments)]
+fn generate_rules<I>(
+    frequent_itemsets: &[ItemSet<I>],
+    support_counts: &SupportCounts<I>,
     min_confidence: f64,
-) -> Vec<(Vec<char>, Vec<char>, f64)> {
+    min_lift: f64,
+    transaction_count: usize,
+    shape: RuleShape,
+    constraints: &ItemConstraints<I>,
+    item_values: Option<&HashMap<I, f64>>,
+) -> Vec<Rule<I>>
+where
+    I: Eq + Hash + Ord + Clone,
+{
     let mut rules = Vec::new();
+    let n = transaction_count as f64;
 
     for itemset in frequent_itemsets {
         if itemset.len() <= 1 {
@@ -127,18 +194,25 @@ fn generate_rules(
 
         let itemset_support = *support_counts.get(itemset).unwrap_or(&0) as f64;
 
-        let subsets = generate_all_subsets(itemset);
+        let subsets = match shape {
+            RuleShape::Any => generate_all_subsets(itemset),
+            RuleShape::SingleConsequent => (0..itemset.len())
+                .map(|skip| {
+                    let mut antecedent = itemset.clone();
+                    antecedent.remove(skip);
+                    antecedent
+                })
+                .collect(),
+        };
 
         for antecedent in &subsets {
-            let antecedent_set: HashSet<_> = antecedent.iter().cloned().collect();
-            let itemset_set: HashSet<_> = itemset.iter().cloned().collect();
-
-            let consequent_set: HashSet<_> =
-                itemset_set.difference(&antecedent_set).cloned().collect();
-            let mut consequent = Vec::from_iter(consequent_set);
-            consequent.sort();
+            let consequent: ItemSet<I> = itemset
+                .iter()
+                .filter(|item| !antecedent.contains(item))
+                .cloned()
+                .collect();
 
-            if consequent.is_empty() {
+            if consequent.is_empty() || !constraints.allows(antecedent, &consequent) {
                 continue;
             }
 
@@ -150,34 +224,245 @@ fn generate_rules(
             let confidence = itemset_support / antecedent_support;
 
             if confidence >= min_confidence {
-                rules.push((antecedent.clone(), consequent, confidence));
+                let consequent_support = *support_counts.get(&consequent).unwrap_or(&0) as f64;
+                let support = itemset_support / n;
+                let antecedent_ratio = antecedent_support / n;
+                let consequent_ratio = consequent_support / n;
+
+                let lift = confidence / consequent_ratio;
+                if lift < min_lift {
+                    continue;
+                }
+
+                let exact = is_exact(confidence);
+                let conviction = if exact {
+                    f64::INFINITY
+                } else {
+                    (1.0 - consequent_ratio) / (1.0 - confidence)
+                };
+
+                let value = value_score(antecedent, &consequent, support, item_values);
+                rules.push(Rule {
+                    antecedent: antecedent.clone(),
+                    consequent,
+                    support,
+                    antecedent_support: antecedent_support as usize,
+                    consequent_support: consequent_support as usize,
+                    confidence,
+                    lift,
+                    leverage: support - antecedent_ratio * consequent_ratio,
+                    conviction,
+                    exact,
+                    value_score: value,
+                    negated_consequent: false,
+                });
+            }
+        }
+    }
+
+    rules
+}
+
+/// Negative rules `x => not y` between frequent items that occur together
+/// less often than independence predicts (lift of `x => y` below 1) and whose
+/// confidence `1 - support(xy) / support(x)` reaches `min_confidence`. Pair
+/// supports come from the level-2 candidates in `support_counts`, which are
+/// counted even when the pair never co-occurs; the rules are flagged
+/// `negated_consequent` and their measures describe the absence of `y`, so
+/// `consequent_support` counts the transactions without `y`.
+fn generate_negative_rules<I>(
+    frequent_itemsets: &[ItemSet<I>],
+    support_counts: &SupportCounts<I>,
+    transaction_count: usize,
+    min_confidence: f64,
+) -> Vec<Rule<I>>
+where
+    I: Eq + Hash + Ord + Clone,
+{
+    let mut rules = Vec::new();
+    let n = transaction_count as f64;
+    let items: Vec<&I> = frequent_itemsets
+        .iter()
+        .filter(|itemset| itemset.len() == 1)
+        .map(|itemset| &itemset[0])
+        .collect();
+
+    for &x in &items {
+        for &y in &items {
+            if x == y {
+                continue;
             }
+
+            let mut pair = vec![x.clone(), y.clone()];
+            pair.sort();
+            let Some(&pair_support) = support_counts.get(&pair) else {
+                continue;
+            };
+            let x_support = *support_counts.get(&vec![x.clone()]).unwrap_or(&0) as f64;
+            let y_support = *support_counts.get(&vec![y.clone()]).unwrap_or(&0) as f64;
+            let pair_support = pair_support as f64;
+
+            if pair_support * n >= x_support * y_support {
+                continue;
+            }
+
+            let confidence = (x_support - pair_support) / x_support;
+            if confidence < min_confidence {
+                continue;
+            }
+
+            let support = (x_support - pair_support) / n;
+            let absent_ratio = 1.0 - y_support / n;
+            let exact = is_exact(confidence);
+            let conviction = if exact {
+                f64::INFINITY
+            } else {
+                (1.0 - absent_ratio) / (1.0 - confidence)
+            };
+
+            rules.push(Rule {
+                antecedent: vec![x.clone()],
+                consequent: vec![y.clone()],
+                support,
+                antecedent_support: x_support as usize,
+                consequent_support: transaction_count - y_support as usize,
+                confidence,
+                lift: confidence / absent_ratio,
+                leverage: support - x_support / n * absent_ratio,
+                conviction,
+                exact,
+                value_score: 0.0,
+                negated_consequent: true,
+            });
         }
     }
 
     rules
 }
 
-fn apriori(
-    transactions: &[Transaction],
+/// Minimal infrequent itemsets: itemsets that are not frequent although all
+/// of their proper subsets are. Singletons come from the level-1 entries of
+/// `support_counts`; longer border itemsets are the candidates generated one
+/// level above each frequent level that did not make it into `frequent`.
+fn negative_border<I>(frequent: &[ItemSet<I>], support_counts: &SupportCounts<I>) -> Vec<ItemSet<I>>
+where
+    I: Eq + Hash + Ord + Clone,
+{
+    let frequent_set: HashSet<&ItemSet<I>> = frequent.iter().collect();
+
+    let mut border: Vec<ItemSet<I>> = support_counts
+        .keys()
+        .filter(|itemset| itemset.len() == 1 && !frequent_set.contains(itemset))
+        .cloned()
+        .collect();
+
+    let max_len = frequent.iter().map(|itemset| itemset.len()).max().unwrap_or(0);
+    for k in 2..=max_len + 1 {
+        let level: Vec<ItemSet<I>> = frequent
+            .iter()
+            .filter(|itemset| itemset.len() == k - 1)
+            .cloned()
+            .collect();
+
+        border.extend(
+            generate_candidates(&level, k)
+                .into_iter()
+                .filter(|candidate| !frequent_set.contains(candidate)),
+        );
+    }
+
+    border.sort();
+    border.dedup();
+    border
+}
+
+/// Candidate and frequent itemset counts for one Apriori level `k`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct LevelStats {
+    k: usize,
+    candidates: usize,
+    frequent: usize,
+}
+
+struct AprioriResult<I = char> {
+    frequent_itemsets: Vec<ItemSet<I>>,
+    support_counts: SupportCounts<I>,
+    rules: Vec<Rule<I>>,
+    levels: Vec<LevelStats>,
+}
+
+/// Dry run of the level-wise search: counts the candidates Apriori would
+/// generate at each level up to `max_k` without building rules, so a run that
+/// is about to explode can be abandoned. Stops early once a level is empty.
+fn estimate_levels<I>(transactions: &[Transaction<I>], min_support: f64, max_k: usize) -> Vec<usize>
+where
+    I: Eq + Hash + Ord + Clone,
+{
+    if max_k == 0 {
+        return Vec::new();
+    }
+
+    let mut singletons: Vec<ItemSet<I>> = transactions
+        .iter()
+        .flatten()
+        .collect::<HashSet<_>>()
+        .into_iter()
+        .map(|item| vec![item.clone()])
+        .collect();
+    singletons.sort();
+
+    let mut estimates = vec![singletons.len()];
+
+    let support_counts = calculate_support(&singletons, transactions);
+    let mut l_prev = get_frequent_itemsets(&singletons, &support_counts, min_support, transactions.len());
+    let mut active_transactions = reduce_transactions(transactions, &l_prev, 1);
+
+    for k in 2..=max_k {
+        let candidates = generate_candidates(&l_prev, k);
+        if candidates.is_empty() {
+            break;
+        }
+        estimates.push(candidates.len());
+
+        let support_counts = calculate_support(&candidates, &active_transactions);
+        l_prev = get_frequent_itemsets(&candidates, &support_counts, min_support, transactions.len());
+        active_transactions = reduce_transactions(&active_transactions, &l_prev, k);
+    }
+
+    estimates
+}
+
+/// Mines itemsets and rules. Transactions can be raw baskets or sets; they
+/// are normalized with `dedup_transactions` first, so repeated items within a
+/// transaction count once, as in FP-Growth.
+fn apriori<I, T>(
+    transactions: &[T],
     min_support: f64,
     min_confidence: f64,
-) -> (
-    Vec<ItemSet>,
-    HashMap<Vec<char>, SupportCount>,
-    Vec<(Vec<char>, Vec<char>, f64)>,
-) {
+    min_lift: f64,
+    max_len: Option<usize>,
+) -> AprioriResult<I>
+where
+    I: Eq + Hash + Ord + Clone,
+    for<'a> &'a T: IntoIterator<Item = &'a I>,
+{
+    let mut baskets: Vec<Vec<I>> = transactions.iter().map(|t| t.into_iter().cloned().collect()).collect();
+    dedup_transactions(&mut baskets);
+    let transactions: Vec<Transaction<I>> =
+        baskets.into_iter().map(|basket| basket.into_iter().collect()).collect();
+    let transactions = transactions.as_slice();
+
     let transaction_count = transactions.len();
 
     let mut unique_items = HashSet::new();
     for t in transactions {
         for item in t {
-            unique_items.insert(*item);
+            unique_items.insert(item);
         }
     }
 
-    let mut singleton_candidates: Vec<ItemSet> =
-        unique_items.iter().map(|item| vec![*item]).collect();
+    let mut singleton_candidates: Vec<ItemSet<I>> =
+        unique_items.into_iter().map(|item| vec![item.clone()]).collect();
 
     singleton_candidates.sort();
 
@@ -190,37 +475,108 @@ fn apriori(
         transaction_count,
     );
 
+    let mut levels = vec![LevelStats {
+        k: 1,
+        candidates: singleton_candidates.len(),
+        frequent: l_prev.len(),
+    }];
+
     let mut k = 2;
     let mut all_frequent_itemsets = l_prev.clone();
+    let mut active_transactions = reduce_transactions(transactions, &l_prev, 1);
 
-    while !l_prev.is_empty() {
+    while !l_prev.is_empty() && max_len.is_none_or(|max_len| k <= max_len) {
         let candidates = generate_candidates(&l_prev, k);
 
         if candidates.is_empty() {
             break;
         }
 
-        let support_counts = calculate_support(&candidates, transactions);
+        let support_counts = calculate_support(&candidates, &active_transactions);
 
         let l_k =
             get_frequent_itemsets(&candidates, &support_counts, min_support, transaction_count);
 
+        levels.push(LevelStats {
+            k,
+            candidates: candidates.len(),
+            frequent: l_k.len(),
+        });
+
         all_frequent_itemsets.extend(l_k.clone());
 
         for (key, value) in support_counts {
             all_support_counts.insert(key, value);
         }
 
+        active_transactions = reduce_transactions(&active_transactions, &l_k, k);
         l_prev = l_k;
         k += 1;
     }
 
-    let rules = generate_rules(&all_frequent_itemsets, &all_support_counts, min_confidence);
+    let rules = generate_rules(
+        &all_frequent_itemsets,
+        &all_support_counts,
+        min_confidence,
+        min_lift,
+        transaction_count,
+        RuleShape::Any,
+        &ItemConstraints::default(),
+        None,
+    );
+
+    AprioriResult {
+        frequent_itemsets: all_frequent_itemsets,
+        support_counts: all_support_counts,
+        rules,
+        levels,
+    }
+}
+
+/// `apriori` over the dense `u32` ids of an `ItemDictionary`, so candidate
+/// joins, subset checks and support counting hash and compare integers
+/// instead of items such as `String`s. Itemsets, supports and rules are mapped
+/// back to the original items; since ids follow item order, they come out in
+/// the same order as from `apriori`.
+fn apriori_indexed<I>(
+    transactions: &[Transaction<I>],
+    min_support: f64,
+    min_confidence: f64,
+    min_lift: f64,
+    max_len: Option<usize>,
+) -> AprioriResult<I>
+where
+    I: Eq + Hash + Ord + Clone,
+{
+    let dictionary = ItemDictionary::from_transactions(transactions);
+    let encoded: Vec<Transaction<u32>> = transactions.iter().map(|t| dictionary.encode(t)).collect();
+
+    let result = apriori(&encoded, min_support, min_confidence, min_lift, max_len);
 
-    (all_frequent_itemsets, all_support_counts, rules)
+    AprioriResult {
+        frequent_itemsets: result
+            .frequent_itemsets
+            .iter()
+            .map(|itemset| dictionary.decode(itemset))
+            .collect(),
+        support_counts: result
+            .support_counts
+            .into_iter()
+            .map(|(itemset, count)| (dictionary.decode(&itemset), count))
+            .collect(),
+        rules: result
+            .rules
+            .into_iter()
+            .map(|rule| rule.map_items(|id| dictionary.item(id).clone()))
+            .collect(),
+        levels: result.levels,
+    }
 }
 
 fn main() {
+    let args = Args::from_env_or_exit(&[cli::MIN_SUPPORT, cli::MIN_CONFIDENCE]);
+    let min_support = args.value_or_exit::<f64>(cli::MIN_SUPPORT).unwrap_or(0.4);
+    let min_confidence = args.value_or_exit::<f64>(cli::MIN_CONFIDENCE).unwrap_or(0.75);
     let transactions: Vec<HashSet<char>> = vec![
         ['a', 'b', 'c', 'd'].iter().cloned().collect(),
         ['b', 'c', 'd'].iter().cloned().collect(),
@@ -234,9 +590,27 @@ fn main() {
         ['e', 'f', 'h'].iter().cloned().collect(),
     ];
 
-    let (frequent_itemsets, support_counts, rules) = apriori(&transactions, 0.4, 0.75);
+    println!(
+        "Estimated candidates per level (up to k = 4): {:?}",
+        estimate_levels(&transactions, 0.4, 4)
+    );
+
+    let AprioriResult {
+        frequent_itemsets,
+        support_counts,
+        rules,
+        levels,
+    } = apriori(&transactions, min_support, min_confidence, 1.0, None);
+
+    println!("Apriori levels:");
+    for level in &levels {
+        println!(
+            "k = {}: {} candidates, {} frequent",
+            level.k, level.candidates, level.frequent
+        );
+    }
 
-    println!("Frequent Itemsets (with support):");
+    println!("\nFrequent Itemsets (with support):");
     for (i, itemset) in frequent_itemsets.iter().enumerate() {
         let support = support_counts.get(itemset).unwrap_or(&0);
         let support_percentage = (*support as f64 / transactions.len() as f64) * 100.0;
@@ -251,13 +625,560 @@ fn main() {
     }
 
     println!("\nAssociation Rules:");
-    for (i, (antecedent, consequent, confidence)) in rules.iter().enumerate() {
+    for (i, rule) in rules.iter().enumerate() {
         println!(
-            "{}. {:?} => {:?} (confidence: {:.2}%)",
+            "{}. {:?} => {:?} (support: {:.1}%, confidence: {:.2}%, lift: {:.2}, leverage: {:.3}, conviction: {:.2})",
             i + 1,
-            antecedent,
-            consequent,
-            confidence * 100.0
+            rule.antecedent,
+            rule.consequent,
+            rule.support * 100.0,
+            rule.confidence * 100.0,
+            rule.lift,
+            rule.leverage,
+            rule.conviction
         );
     }
+
+    println!("\nExact rules (confidence 100%):");
+    for rule in rules.iter().filter(|r| r.exact) {
+        println!("{:?} => {:?}", rule.antecedent, rule.consequent);
+    }
+
+    let general_rules = filter_redundant(rules.clone());
+    println!(
+        "\nNon-redundant rules ({} of {} kept):",
+        general_rules.len(),
+        rules.len()
+    );
+    for rule in &general_rules {
+        println!(
+            "{:?} => {:?} (confidence: {:.2}%)",
+            rule.antecedent,
+            rule.consequent,
+            rule.confidence * 100.0
+        );
+    }
+
+    println!("\nRules with minimal antecedents (confidence >= 50%):");
+    let confident_rules = generate_rules(
+        &frequent_itemsets,
+        &support_counts,
+        0.5,
+        0.0,
+        transactions.len(),
+        RuleShape::Any,
+        &ItemConstraints::default(),
+        None,
+    );
+    for rule in minimal_antecedent_rules(confident_rules) {
+        println!(
+            "{:?} => {:?} (confidence: {:.2}%)",
+            rule.antecedent,
+            rule.consequent,
+            rule.confidence * 100.0
+        );
+    }
+
+    println!("\nExact rules checked against held-out transactions:");
+    let held_out = vec![
+        vec!['b', 'c', 'd'],
+        vec!['c', 'd'],
+        vec!['c', 'e'],
+        vec!['a', 'b', 'd'],
+    ];
+    let exact_rules: Vec<Rule> = rules.iter().filter(|rule| rule.exact).cloned().collect();
+    for (rule, evaluation) in exact_rules.iter().zip(evaluate_rules(&exact_rules, &held_out)) {
+        println!(
+            "{:?} => {:?}: confidence {:.2}% -> {:.2}%, support {:.1}% -> {:.1}%",
+            rule.antecedent,
+            rule.consequent,
+            rule.confidence * 100.0,
+            evaluation.confidence * 100.0,
+            rule.support * 100.0,
+            evaluation.support * 100.0
+        );
+    }
+
+    println!("\nRules as JSON:\n{}", rules_to_json(&rules));
+
+    print!("\nRules as CSV:\n{}", rules_to_csv(&rules));
+
+    println!("\nRules predicting 'd' (confidence >= 50%):");
+    let constraints = ItemConstraints {
+        require_consequent: Some(HashSet::from(['d'])),
+        forbid_antecedent: None,
+        item_parent: None,
+    };
+    for rule in generate_rules(
+        &frequent_itemsets,
+        &support_counts,
+        0.5,
+        0.0,
+        transactions.len(),
+        RuleShape::Any,
+        &constraints,
+        None,
+    ) {
+        println!(
+            "{:?} => {:?} (confidence: {:.2}%)",
+            rule.antecedent,
+            rule.consequent,
+            rule.confidence * 100.0
+        );
+    }
+
+    println!("\nSingle-consequent rules:");
+    let single_consequent_rules = generate_rules(
+        &frequent_itemsets,
+        &support_counts,
+        0.75,
+        1.0,
+        transactions.len(),
+        RuleShape::SingleConsequent,
+        &ItemConstraints::default(),
+        None,
+    );
+    for rule in &single_consequent_rules {
+        println!(
+            "{:?} => {:?} (confidence: {:.2}%)",
+            rule.antecedent,
+            rule.consequent,
+            rule.confidence * 100.0
+        );
+    }
+
+    let low_support = apriori(&transactions, 0.2, 0.5, 0.0, None);
+    let item_values = HashMap::from([('a', 1.0), ('b', 1.0), ('c', 1.0), ('d', 1.0), ('h', 40.0)]);
+    let valued_rules = generate_rules(
+        &low_support.frequent_itemsets,
+        &low_support.support_counts,
+        0.5,
+        0.0,
+        transactions.len(),
+        RuleShape::Any,
+        &ItemConstraints::default(),
+        Some(&item_values),
+    );
+    for (label, by) in [("support", RankBy::Support), ("value", RankBy::Value)] {
+        println!("\nTop 3 rules by {}:", label);
+        for rule in top_rules(&valued_rules, 3, by) {
+            println!(
+                "{:?} => {:?} (support: {:.1}%, value score: {:.2})",
+                rule.antecedent,
+                rule.consequent,
+                rule.support * 100.0,
+                rule.value_score
+            );
+        }
+    }
+
+    println!("\nNegative rules (confidence 100%):");
+    for rule in generate_negative_rules(&frequent_itemsets, &support_counts, transactions.len(), 1.0) {
+        println!(
+            "{:?} => not {:?} (support: {:.1}%, lift: {:.2})",
+            rule.antecedent,
+            rule.consequent,
+            rule.support * 100.0,
+            rule.lift
+        );
+    }
+
+    println!("\nNegative border:");
+    for itemset in negative_border(&frequent_itemsets, &support_counts) {
+        println!("{:?} (support: {})", itemset, support_counts.get(&itemset).unwrap_or(&0));
+    }
+
+    let word_transactions: Vec<HashSet<String>> = read_transactions_json(
+        r#"[["bread", "milk"], ["bread", "butter", "milk"], ["butter", "milk"], ["bread", "butter"]]"#,
+    )
+    .expect("embedded JSON transactions are valid")
+    .into_iter()
+    .map(|t| t.into_iter().collect())
+    .collect();
+
+    let word_rules = apriori_indexed(&word_transactions, 0.5, 0.6, 0.0, None).rules;
+    println!("\nRules over String items (mined via dictionary ids):");
+    for rule in &word_rules {
+        println!(
+            "{:?} => {:?} (confidence: {:.2}%)",
+            rule.antecedent,
+            rule.consequent,
+            rule.confidence * 100.0
+        );
+    }
+
+    let seasoning_transactions: Vec<HashSet<String>> = read_transactions_json(
+        r#"[["salt, coarse", "pepper"], ["salt, coarse", "pepper", "oil"], ["oil"]]"#,
+    )
+    .expect("embedded JSON transactions are valid")
+    .into_iter()
+    .map(|t| t.into_iter().collect())
+    .collect();
+    print!(
+        "\nCSV with an item containing the delimiter:\n{}",
+        rules_to_csv(&apriori(&seasoning_transactions, 0.6, 1.0, 0.0, None).rules)
+    );
+
+    let staple_transactions: Vec<HashSet<char>> = vec![
+        ['a', 'm'].iter().cloned().collect(),
+        ['a', 'm'].iter().cloned().collect(),
+        ['a', 'b', 'c', 'm'].iter().cloned().collect(),
+        ['b', 'c', 'm'].iter().cloned().collect(),
+        ['m'].iter().cloned().collect(),
+    ];
+    for min_lift in [1.0, 1.2] {
+        println!("\nRules with min_lift = {} where 'm' is in every transaction:", min_lift);
+        for rule in apriori(&staple_transactions, 0.4, 0.9, min_lift, None).rules {
+            println!(
+                "{:?} => {:?} (confidence: {:.2}%, lift: {:.2})",
+                rule.antecedent,
+                rule.consequent,
+                rule.confidence * 100.0,
+                rule.lift
+            );
+        }
+    }
+
+    let baskets = vec![vec!['a', 'a', 'b'], vec!['b']];
+    let basket_support = apriori(&baskets, 0.5, 1.0, 1.0, None).support_counts;
+    println!(
+        "\nSupport of ['a'] in [a, a, b] and [b]: {}",
+        basket_support.get(&vec!['a']).unwrap_or(&0)
+    );
+
+    let (eclat_itemsets, _) = eclat(&transactions, 0.4);
+    println!("\nEclat found {} frequent itemsets", eclat_itemsets.len());
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fmt;
+
+    use super::*;
+
+    fn sample_transactions() -> Vec<Transaction> {
+        vec![
+            HashSet::from(['a', 'b', 'c', 'd']),
+            HashSet::from(['b', 'c', 'd']),
+            HashSet::from(['a', 'e', 'f', 'g', 'h']),
+            HashSet::from(['b', 'c', 'd', 'e', 'g', 'j']),
+            HashSet::from(['b', 'c', 'd', 'e', 'f']),
+            HashSet::from(['a', 'f', 'g']),
+            HashSet::from(['a', 'i', 'j']),
+            HashSet::from(['a', 'b', 'e', 'h']),
+            HashSet::from(['f', 'g', 'h', 'i', 'j']),
+            HashSet::from(['e', 'f', 'h']),
+        ]
+    }
+
+    #[test]
+    fn repeated_items_count_once() {
+        let baskets = vec![vec!['a', 'a', 'b'], vec!['b']];
+        let support_counts = apriori(&baskets, 0.5, 1.0, 1.0, None).support_counts;
+
+        assert_eq!(support_counts[&vec!['a']], 1);
+        assert_eq!(support_counts[&vec!['b']], 2);
+    }
+
+    #[test]
+    fn confidence_is_the_itemset_support_over_the_antecedent_support() {
+        let transactions = sample_transactions();
+        let n = transactions.len() as f64;
+        let rules = apriori(&transactions, 0.2, 0.5, 0.0, None).rules;
+
+        assert!(!rules.is_empty());
+        for rule in &rules {
+            let itemset_support = rule.support * n;
+            assert!((itemset_support / rule.antecedent_support as f64 - rule.confidence).abs() < 1e-9);
+            assert!(rule.consequent_support >= itemset_support.round() as usize);
+        }
+    }
+
+    fn assert_same_as_direct<I: Eq + Hash + Ord + Clone + fmt::Debug>(
+        transactions: &[Transaction<I>],
+        min_support: f64,
+        min_confidence: f64,
+    ) {
+        let direct = apriori(transactions, min_support, min_confidence, 0.0, None);
+        let indexed = apriori_indexed(transactions, min_support, min_confidence, 0.0, None);
+
+        assert_eq!(indexed.frequent_itemsets, direct.frequent_itemsets);
+        assert_eq!(indexed.support_counts, direct.support_counts);
+        assert_eq!(indexed.rules, direct.rules);
+        assert_eq!(indexed.levels, direct.levels);
+    }
+
+    #[test]
+    fn mining_dictionary_ids_matches_mining_the_items() {
+        assert_same_as_direct(&sample_transactions(), 0.4, 0.75);
+
+        let words: Vec<Transaction<String>> = read_transactions_json(
+            r#"[["bread", "milk"], ["bread", "butter", "milk"], ["butter", "milk"], ["bread", "butter"]]"#,
+        )
+        .unwrap()
+        .into_iter()
+        .map(|t| t.into_iter().collect())
+        .collect();
+        assert_same_as_direct(&words, 0.5, 0.6);
+    }
+
+    #[test]
+    fn eclat_finds_the_apriori_itemsets_and_supports() {
+        let transactions = sample_transactions();
+        let mut expected = apriori(&transactions, 0.4, 0.75, 1.0, None);
+        let (mut itemsets, support_counts) = eclat(&transactions, 0.4);
+
+        expected.frequent_itemsets.sort();
+        itemsets.sort();
+        assert_eq!(itemsets, expected.frequent_itemsets);
+        for itemset in &itemsets {
+            assert_eq!(support_counts[itemset], expected.support_counts[itemset], "{:?}", itemset);
+        }
+    }
+
+    #[test]
+    fn independent_items_have_lift_one() {
+        // a and b each occur in half the transactions and together in a quarter.
+        let transactions = vec![
+            HashSet::from(['a', 'b']),
+            HashSet::from(['a', 'c']),
+            HashSet::from(['b', 'c']),
+            HashSet::from(['c']),
+        ];
+        let rules = apriori(&transactions, 0.25, 0.5, 0.0, None).rules;
+
+        let a_to_b = rules.iter().find(|r| r.antecedent == ['a'] && r.consequent == ['b']).unwrap();
+        assert!((a_to_b.lift - 1.0).abs() < 1e-12);
+        assert!(a_to_b.leverage.abs() < 1e-12);
+        assert!((a_to_b.conviction - 1.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn frequent_itemsets_match_a_brute_force_search() {
+        let transactions = sample_transactions();
+        let mut items: Vec<char> = transactions.iter().flatten().copied().collect();
+        items.sort();
+        items.dedup();
+        let min_count = (0.2 * transactions.len() as f64).ceil() as usize;
+
+        let mut expected: Vec<ItemSet> = generate_all_subsets(&items)
+            .into_iter()
+            .chain(std::iter::once(items.clone()))
+            .filter(|itemset| {
+                transactions.iter().filter(|t| itemset.iter().all(|item| t.contains(item))).count() >= min_count
+            })
+            .collect();
+        expected.sort();
+
+        let mut found = apriori(&transactions, 0.2, 1.0, 0.0, None).frequent_itemsets;
+        found.sort();
+        assert_eq!(found, expected);
+    }
+
+    #[test]
+    fn transaction_reduction_changes_no_output() {
+        let transactions = sample_transactions();
+        let n = transactions.len();
+
+        // The level-wise search with every level counted on all transactions.
+        let singletons: Vec<ItemSet> = "abcdefghij".chars().map(|item| vec![item]).collect();
+        let mut support_counts = calculate_support(&singletons, &transactions);
+        let mut l_prev = get_frequent_itemsets(&singletons, &support_counts, 0.2, n);
+        let mut frequent = l_prev.clone();
+        for k in 2.. {
+            let candidates = generate_candidates(&l_prev, k);
+            if candidates.is_empty() {
+                break;
+            }
+            let counts = calculate_support(&candidates, &transactions);
+            l_prev = get_frequent_itemsets(&candidates, &counts, 0.2, n);
+            frequent.extend(l_prev.clone());
+            support_counts.extend(counts);
+        }
+        let rules = generate_rules(
+            &frequent,
+            &support_counts,
+            0.5,
+            0.0,
+            n,
+            RuleShape::Any,
+            &ItemConstraints::default(),
+            None,
+        );
+
+        let reduced = apriori(&transactions, 0.2, 0.5, 0.0, None);
+        assert_eq!(reduced.frequent_itemsets, frequent);
+        assert_eq!(reduced.support_counts, support_counts);
+        assert_eq!(reduced.rules, rules);
+    }
+
+    #[test]
+    fn level_one_candidates_are_the_distinct_items() {
+        let transactions = sample_transactions();
+        let distinct = transactions.iter().flatten().collect::<HashSet<_>>().len();
+
+        let levels = apriori(&transactions, 0.4, 0.75, 1.0, None).levels;
+        assert_eq!(distinct, 10);
+        assert_eq!(levels[0], LevelStats { k: 1, candidates: distinct, frequent: 8 });
+        assert!(levels.windows(2).all(|w| w[1].k == w[0].k + 1));
+    }
+
+    #[test]
+    fn estimate_starts_with_the_distinct_items_and_matches_the_real_run() {
+        let transactions = sample_transactions();
+        let distinct = transactions.iter().flatten().collect::<HashSet<_>>().len();
+
+        let estimates = estimate_levels(&transactions, 0.4, 4);
+        let levels = apriori(&transactions, 0.4, 0.75, 1.0, Some(4)).levels;
+        assert_eq!(estimates[0], distinct);
+        assert_eq!(estimates, levels.iter().map(|level| level.candidates).collect::<Vec<_>>());
+        assert!(estimate_levels(&transactions, 0.4, 0).is_empty());
+    }
+
+    #[test]
+    fn every_negative_border_itemset_is_minimal_infrequent() {
+        let transactions = vec![
+            HashSet::from(['a', 'b', 'c']),
+            HashSet::from(['a', 'b']),
+            HashSet::from(['a', 'c']),
+            HashSet::from(['b', 'd']),
+        ];
+        let result = apriori(&transactions, 0.5, 1.0, 0.0, None);
+        let frequent: HashSet<&ItemSet> = result.frequent_itemsets.iter().collect();
+
+        let border = negative_border(&result.frequent_itemsets, &result.support_counts);
+        assert_eq!(border, vec![vec!['b', 'c'], vec!['d']]);
+        for itemset in &border {
+            assert!(!frequent.contains(itemset));
+            if itemset.len() > 1 {
+                assert!(generate_all_subsets(itemset).iter().all(|subset| frequent.contains(subset)));
+            }
+        }
+    }
+
+    #[test]
+    fn single_consequent_rules_conclude_one_item() {
+        let result = apriori(&sample_transactions(), 0.2, 0.5, 0.0, None);
+        let rules_with = |shape| {
+            generate_rules(
+                &result.frequent_itemsets,
+                &result.support_counts,
+                0.5,
+                0.0,
+                10,
+                shape,
+                &ItemConstraints::default(),
+                None,
+            )
+        };
+
+        let split = |rule: &Rule| (rule.antecedent.clone(), rule.consequent.clone());
+        let mut all = rules_with(RuleShape::Any);
+        let mut single = rules_with(RuleShape::SingleConsequent);
+        assert!(all.iter().any(|rule| rule.consequent.len() > 1));
+        assert!(single.iter().all(|rule| rule.consequent.len() == 1));
+
+        all.retain(|rule| rule.consequent.len() == 1);
+        all.sort_by_key(split);
+        single.sort_by_key(split);
+        assert_eq!(single, all);
+    }
+
+    #[test]
+    fn min_lift_drops_a_confident_rule_into_a_staple_item() {
+        // 'm' is in every transaction, so a => m is exact but has lift 1.
+        let transactions = vec![
+            HashSet::from(['a', 'm']),
+            HashSet::from(['a', 'm']),
+            HashSet::from(['a', 'b', 'c', 'm']),
+            HashSet::from(['b', 'c', 'm']),
+            HashSet::from(['m']),
+        ];
+        let has_rule = |rules: &[Rule], antecedent: &[char], consequent: &[char]| {
+            rules.iter().any(|r| r.antecedent == antecedent && r.consequent == consequent)
+        };
+
+        let rules = apriori(&transactions, 0.4, 0.9, 1.0, None).rules;
+        assert!(has_rule(&rules, &['a'], &['m']));
+        let lifted = apriori(&transactions, 0.4, 0.9, 1.2, None).rules;
+        assert!(!has_rule(&lifted, &['a'], &['m']));
+        assert!(has_rule(&lifted, &['b'], &['c']));
+        assert!(lifted.iter().all(|rule| rule.lift >= 1.2));
+    }
+
+    #[test]
+    fn max_len_caps_the_itemset_length() {
+        let transactions = sample_transactions();
+        let uncapped = apriori(&transactions, 0.2, 0.5, 0.0, None);
+        let capped = apriori(&transactions, 0.2, 0.5, 0.0, Some(2));
+
+        assert!(uncapped.frequent_itemsets.iter().any(|itemset| itemset.len() > 2));
+        assert!(capped.frequent_itemsets.iter().all(|itemset| itemset.len() <= 2));
+        assert_eq!(
+            capped.frequent_itemsets,
+            uncapped.frequent_itemsets.into_iter().filter(|itemset| itemset.len() <= 2).collect::<Vec<_>>()
+        );
+        assert!(capped.rules.iter().all(|rule| rule.antecedent.len() + rule.consequent.len() <= 2));
+    }
+
+    #[test]
+    fn only_the_deterministic_implication_is_exact() {
+        // Every transaction with 'a' also has 'b'.
+        let transactions = vec![
+            HashSet::from(['a', 'b']),
+            HashSet::from(['a', 'b', 'c']),
+            HashSet::from(['b', 'c']),
+            HashSet::from(['c']),
+        ];
+        let rules = apriori(&transactions, 0.5, 0.0, 0.0, None).rules;
+
+        assert_eq!(rules.len(), 4);
+        let exact: Vec<&Rule> = rules.iter().filter(|rule| rule.exact).collect();
+        assert_eq!(exact.len(), 1);
+        assert_eq!((exact[0].antecedent.as_slice(), exact[0].consequent.as_slice()), (&['a'][..], &['b'][..]));
+        assert_eq!(exact[0].conviction, f64::INFINITY);
+    }
+
+    #[test]
+    fn required_consequent_yields_only_rules_about_it() {
+        let result = apriori(&sample_transactions(), 0.2, 0.5, 0.0, None);
+        let constraints = ItemConstraints {
+            require_consequent: Some(HashSet::from(['e'])),
+            forbid_antecedent: Some(HashSet::from(['h'])),
+            item_parent: None,
+        };
+        let rules = generate_rules(
+            &result.frequent_itemsets,
+            &result.support_counts,
+            0.5,
+            0.0,
+            10,
+            RuleShape::Any,
+            &constraints,
+            None,
+        );
+
+        assert!(!rules.is_empty());
+        assert!(rules.iter().all(|rule| rule.consequent == ['e']));
+        assert!(rules.iter().all(|rule| !rule.antecedent.contains(&'h')));
+    }
+
+    #[test]
+    fn items_that_never_co_occur_give_a_strong_negative_rule() {
+        let transactions = vec![
+            HashSet::from(['a', 'c']),
+            HashSet::from(['a', 'c']),
+            HashSet::from(['b', 'c']),
+            HashSet::from(['b']),
+        ];
+        let result = apriori(&transactions, 0.5, 1.0, 0.0, None);
+        let rules = generate_negative_rules(&result.frequent_itemsets, &result.support_counts, 4, 0.9);
+
+        let a_not_b = rules.iter().find(|r| r.antecedent == ['a'] && r.consequent == ['b']).unwrap();
+        assert!(a_not_b.negated_consequent && a_not_b.exact);
+        assert_eq!(a_not_b.confidence, 1.0);
+        assert_eq!(a_not_b.support, 0.5);
+        assert_eq!(a_not_b.lift, 2.0);
+        assert!(rules.iter().all(|rule| rule.negated_consequent));
+    }
 }
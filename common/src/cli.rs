@@ -0,0 +1,200 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::ops::RangeInclusive;
+use std::str::FromStr;
+
+/// A `--name <value>` option a binary accepts, with its usage line.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Flag {
+    pub name: &'static str,
+    pub help: &'static str,
+}
+
+pub const INPUT: Flag = Flag {
+    name: "input",
+    help: "path to the input file (may also be given as the first positional argument)",
+};
+pub const MIN_SUPPORT: Flag = Flag {
+    name: "min-support",
+    help: "minimum relative support of a frequent itemset",
+};
+pub const MIN_CONFIDENCE: Flag = Flag {
+    name: "min-confidence",
+    help: "minimum confidence of an association rule",
+};
+pub const EPS: Flag = Flag {
+    name: "eps",
+    help: "DBSCAN neighborhood radius",
+};
+pub const MIN_POINTS: Flag = Flag {
+    name: "min-points",
+    help: "DBSCAN neighbors required for a core point",
+};
+pub const K: Flag = Flag {
+    name: "k",
+    help: "number of clusters",
+};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum CliError {
+    UnknownFlag(String),
+    MissingValue(String),
+    InvalidValue { flag: String, value: String },
+    UnexpectedArgument(String),
+}
+
+impl fmt::Display for CliError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CliError::UnknownFlag(flag) => write!(f, "unknown option '{}'", flag),
+            CliError::MissingValue(flag) => write!(f, "option '--{}' needs a value", flag),
+            CliError::InvalidValue { flag, value } => {
+                write!(f, "invalid value '{}' for option '--{}'", value, flag)
+            }
+            CliError::UnexpectedArgument(arg) => write!(f, "unexpected argument '{}'", arg),
+        }
+    }
+}
+
+impl std::error::Error for CliError {}
+
+/// Parsed `--name value` options. Values are kept as strings and converted
+/// on lookup, so each binary decides the type and the default of its flags.
+#[derive(Debug, Clone)]
+pub struct Args {
+    usage: String,
+    values: HashMap<&'static str, String>,
+}
+
+impl Args {
+    /// Parses `args`, not including the program name, against `flags`. A
+    /// single bare argument is accepted as `--input` when that flag is allowed,
+    /// matching the older `binary <path>` invocation.
+    pub fn parse<I>(program: &str, args: I, flags: &[Flag]) -> Result<Args, CliError>
+    where
+        I: IntoIterator<Item = String>,
+    {
+        let mut values = HashMap::new();
+        let mut args = args.into_iter();
+
+        while let Some(arg) = args.next() {
+            let flag = match arg.strip_prefix("--") {
+                Some(name) => flags
+                    .iter()
+                    .find(|flag| flag.name == name)
+                    .ok_or_else(|| CliError::UnknownFlag(arg.clone()))?,
+                None if flags.contains(&INPUT) && !values.contains_key(INPUT.name) => {
+                    values.insert(INPUT.name, arg);
+                    continue;
+                }
+                None => return Err(CliError::UnexpectedArgument(arg)),
+            };
+
+            let value = args.next().ok_or_else(|| CliError::MissingValue(flag.name.to_string()))?;
+            values.insert(flag.name, value);
+        }
+
+        Ok(Args {
+            usage: usage(program, flags),
+            values,
+        })
+    }
+
+    /// `parse` on the process arguments. On error prints the message and the
+    /// usage to stderr and exits with status 2.
+    pub fn from_env_or_exit(flags: &[Flag]) -> Args {
+        let mut args = std::env::args();
+        let program = args.next().unwrap_or_default();
+        Args::parse(&program, args, flags).unwrap_or_else(|err| exit_with_usage(&err, &usage(&program, flags)))
+    }
+
+    /// The value of `flag` converted to `T`, or `None` if it was not given.
+    pub fn value<T: FromStr>(&self, flag: Flag) -> Result<Option<T>, CliError> {
+        self.values
+            .get(flag.name)
+            .map(|value| {
+                value.parse().map_err(|_| CliError::InvalidValue {
+                    flag: flag.name.to_string(),
+                    value: value.clone(),
+                })
+            })
+            .transpose()
+    }
+
+    /// `value`, printing the usage and exiting with status 2 on a value that
+    /// does not parse.
+    pub fn value_or_exit<T: FromStr>(&self, flag: Flag) -> Option<T> {
+        self.value(flag).unwrap_or_else(|err| exit_with_usage(&err, &self.usage))
+    }
+
+    /// `value`, also rejecting a value that parses but lies outside `range`.
+    pub fn value_in<T: FromStr + PartialOrd>(
+        &self,
+        flag: Flag,
+        range: RangeInclusive<T>,
+    ) -> Result<Option<T>, CliError> {
+        match self.value(flag)? {
+            Some(value) if !range.contains(&value) => Err(CliError::InvalidValue {
+                flag: flag.name.to_string(),
+                value: self.values[flag.name].clone(),
+            }),
+            value => Ok(value),
+        }
+    }
+
+    /// `value_in`, printing the usage and exiting with status 2 on a value
+    /// that does not parse or is out of range.
+    pub fn value_in_or_exit<T: FromStr + PartialOrd>(&self, flag: Flag, range: RangeInclusive<T>) -> Option<T> {
+        self.value_in(flag, range).unwrap_or_else(|err| exit_with_usage(&err, &self.usage))
+    }
+}
+
+/// One line per flag, in the order given.
+pub fn usage(program: &str, flags: &[Flag]) -> String {
+    let mut text = format!("usage: {} [options]\n", program);
+    for flag in flags {
+        text.push_str(&format!("  --{:<16} {}\n", flag.name, flag.help));
+    }
+    text
+}
+
+fn exit_with_usage(err: &CliError, usage: &str) -> ! {
+    eprintln!("error: {}\n{}", err, usage);
+    std::process::exit(2)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Args, CliError> {
+        Args::parse("test", args.iter().map(|arg| arg.to_string()), &[INPUT, K])
+    }
+
+    #[test]
+    fn k_must_be_a_number() {
+        let err = parse(&["--k", "abc"]).unwrap().value::<usize>(K).unwrap_err();
+        assert_eq!(
+            err,
+            CliError::InvalidValue {
+                flag: "k".to_string(),
+                value: "abc".to_string()
+            }
+        );
+        assert_eq!(parse(&["--k", "3"]).unwrap().value::<usize>(K), Ok(Some(3)));
+    }
+
+    #[test]
+    fn k_outside_the_range_is_rejected() {
+        assert!(parse(&["--k", "0"]).unwrap().value_in::<usize>(K, 1..=5).is_err());
+        assert!(parse(&["--k", "6"]).unwrap().value_in::<usize>(K, 1..=5).is_err());
+        assert_eq!(parse(&["--k", "5"]).unwrap().value_in::<usize>(K, 1..=5), Ok(Some(5)));
+        assert_eq!(parse(&[]).unwrap().value_in::<usize>(K, 1..=5), Ok(None));
+    }
+
+    #[test]
+    fn unknown_flags_and_missing_values_are_errors() {
+        assert_eq!(parse(&["--eps", "1"]).unwrap_err(), CliError::UnknownFlag("--eps".to_string()));
+        assert_eq!(parse(&["--k"]).unwrap_err(), CliError::MissingValue("k".to_string()));
+    }
+}
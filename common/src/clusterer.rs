@@ -0,0 +1,75 @@
+use std::collections::HashSet;
+
+use crate::metrics::{davies_bouldin, silhouette_score};
+use crate::Point;
+
+/// A clustering algorithm behind one noise-aware signature, so evaluation
+/// code can treat k-means, DBSCAN and hierarchical cuts alike. Algorithms
+/// without a notion of noise label every point `Some`.
+pub trait Clusterer {
+    fn fit(&mut self, data: &[Point]) -> Vec<Option<usize>>;
+}
+
+/// Fits `clusterer` on `data` and scores the result as
+/// `(silhouette_score, davies_bouldin)`. Noise points are left out of both.
+pub fn evaluate(clusterer: &mut dyn Clusterer, data: &[Point]) -> (f64, f64) {
+    let labels = clusterer.fit(data);
+    score(data, &labels)
+}
+
+/// `(silhouette_score, davies_bouldin)` of a noise-aware labelling, with the
+/// noise points left out.
+fn score(data: &[Point], labels: &[Option<usize>]) -> (f64, f64) {
+    let (clustered, labels): (Vec<Point>, Vec<usize>) = data
+        .iter()
+        .zip(labels)
+        .filter_map(|(point, label)| label.map(|label| (point.clone(), label)))
+        .unzip();
+
+    (silhouette_score(&clustered, &labels), davies_bouldin(&clustered, &labels))
+}
+
+/// A plain-text table comparing labellings of the same `data`, one row per
+/// `(name, labels)` pair in the order given: cluster count, noise count,
+/// silhouette and Davies-Bouldin, the last two scored as in `evaluate`.
+pub fn compare_clusterings(data: &[Point], reports: &[(&str, Vec<Option<usize>>)]) -> String {
+    let width = reports.iter().map(|(name, _)| name.len()).max().unwrap_or(0).max("algorithm".len());
+
+    let mut table = format!(
+        "{:<width$}  {:>8}  {:>5}  {:>10}  {:>14}\n",
+        "algorithm", "clusters", "noise", "silhouette", "Davies-Bouldin"
+    );
+    for (name, labels) in reports {
+        let clusters = labels.iter().flatten().collect::<HashSet<_>>().len();
+        let noise = labels.iter().filter(|label| label.is_none()).count();
+        let (silhouette, db_index) = score(data, labels);
+        table.push_str(&format!(
+            "{:<width$}  {:>8}  {:>5}  {:>10.4}  {:>14.4}\n",
+            name, clusters, noise, silhouette, db_index
+        ));
+    }
+    table
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn comparison_has_a_row_per_labelling_with_its_counts() {
+        let data: Vec<Point> = [(0.0, 0.0), (0.1, 0.0), (5.0, 5.0), (5.1, 5.0), (9.0, 0.0)]
+            .into_iter()
+            .map(Point::from)
+            .collect();
+        let reports = [
+            ("two clusters", vec![Some(0), Some(0), Some(1), Some(1), None]),
+            ("three clusters", vec![Some(0), Some(0), Some(1), Some(1), Some(2)]),
+        ];
+
+        let table = compare_clusterings(&data, &reports);
+        let rows: Vec<Vec<&str>> = table.lines().skip(1).map(|line| line.split_whitespace().collect()).collect();
+        assert_eq!(table.lines().count(), reports.len() + 1);
+        assert_eq!(rows[0][..4], ["two", "clusters", "2", "1"]);
+        assert_eq!(rows[1][..4], ["three", "clusters", "3", "0"]);
+    }
+}
@@ -0,0 +1,186 @@
+use crate::metrics::{bray_curtis, canberra, minkowski};
+use crate::Point;
+
+/// A dissimilarity between points. Algorithms that only compare points, like
+/// k-medoids, accept any implementation rather than assuming Euclidean space.
+pub trait Distance {
+    fn distance(&self, a: &Point, b: &Point) -> f64;
+}
+
+/// Sorts distances ascending by `f64::total_cmp`, which never panics: a NaN
+/// distance sorts after infinity (a negative NaN before everything), so the
+/// order is the same whatever order the values came in.
+pub fn sort_distances(distances: &mut [f64]) {
+    distances.sort_by(f64::total_cmp);
+}
+
+/// All pairwise distances under `metric`. Only the upper triangle is
+/// computed; it is mirrored below the diagonal and the diagonal is zero.
+pub fn distance_matrix(data: &[Point], metric: &dyn Distance) -> Vec<Vec<f64>> {
+    let n = data.len();
+    let mut distances = vec![vec![0.0; n]; n];
+    for i in 0..n {
+        for j in i + 1..n {
+            let distance = metric.distance(&data[i], &data[j]);
+            distances[i][j] = distance;
+            distances[j][i] = distance;
+        }
+    }
+    distances
+}
+
+pub struct Euclidean;
+
+impl Distance for Euclidean {
+    fn distance(&self, a: &Point, b: &Point) -> f64 {
+        a.distance(b)
+    }
+}
+
+pub struct Manhattan;
+
+impl Distance for Manhattan {
+    fn distance(&self, a: &Point, b: &Point) -> f64 {
+        a.check_dim(b);
+        a.coords.iter().zip(&b.coords).map(|(x, y)| (x - y).abs()).sum()
+    }
+}
+
+/// The Minkowski family of distances with exponent `p >= 1`; see
+/// `metrics::minkowski`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Minkowski {
+    pub p: f64,
+}
+
+impl Minkowski {
+    pub fn new(p: f64) -> Self {
+        assert!(p >= 1.0, "Minkowski exponent p must be at least 1, got {}", p);
+        Minkowski { p }
+    }
+}
+
+impl Distance for Minkowski {
+    fn distance(&self, a: &Point, b: &Point) -> f64 {
+        minkowski(a, b, self.p)
+    }
+}
+
+/// The metrics DBSCAN and hierarchical clustering can be configured with.
+/// `Canberra` and `BrayCurtis` suit compositional data such as proportions;
+/// see `metrics::canberra` and `metrics::bray_curtis`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Metric {
+    Minkowski(Minkowski),
+    Canberra,
+    BrayCurtis,
+}
+
+impl From<Minkowski> for Metric {
+    fn from(minkowski: Minkowski) -> Self {
+        Metric::Minkowski(minkowski)
+    }
+}
+
+impl Distance for Metric {
+    fn distance(&self, a: &Point, b: &Point) -> f64 {
+        match self {
+            Metric::Minkowski(minkowski) => minkowski.distance(a, b),
+            Metric::Canberra => canberra(a, b),
+            Metric::BrayCurtis => bray_curtis(a, b),
+        }
+    }
+}
+
+/// Euclidean distance over numeric coordinates combined with a 0/1 mismatch
+/// for categorical ones: coordinate `i` is categorical when `categorical[i]`
+/// is set, and then contributes 1 to the squared sum if the codes differ.
+pub struct MixedDistance {
+    pub categorical: Vec<bool>,
+}
+
+impl MixedDistance {
+    pub fn new(categorical: Vec<bool>) -> Self {
+        MixedDistance { categorical }
+    }
+}
+
+impl Distance for MixedDistance {
+    fn distance(&self, a: &Point, b: &Point) -> f64 {
+        a.check_dim(b);
+        assert_eq!(
+            self.categorical.len(),
+            a.dim(),
+            "categorical mask covers {} coordinates but points have {}",
+            self.categorical.len(),
+            a.dim()
+        );
+
+        a.coords
+            .iter()
+            .zip(&b.coords)
+            .zip(&self.categorical)
+            .map(|((x, y), &categorical)| match categorical {
+                true if x == y => 0.0,
+                true => 1.0,
+                false => (x - y) * (x - y),
+            })
+            .sum::<f64>()
+            .sqrt()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sort_distances_puts_nan_last_whatever_the_input_order() {
+        let mut distances = vec![2.0, f64::NAN, 0.5, f64::INFINITY, 1.0];
+        let mut reversed: Vec<f64> = distances.iter().rev().copied().collect();
+        sort_distances(&mut distances);
+        sort_distances(&mut reversed);
+
+        assert_eq!(distances[..4], [0.5, 1.0, 2.0, f64::INFINITY]);
+        assert!(distances[4].is_nan());
+        let bits = |values: &[f64]| values.iter().map(|value| value.to_bits()).collect::<Vec<_>>();
+        assert_eq!(bits(&distances), bits(&reversed));
+    }
+
+    #[test]
+    fn mixed_distance_counts_a_category_mismatch_as_one() {
+        // (numeric, category code)
+        let mixed = MixedDistance::new(vec![false, true]);
+        let (a, b, c) = (Point::new(1.0, 3.0), Point::new(4.0, 3.0), Point::new(1.0, 7.0));
+
+        assert_eq!(mixed.distance(&a, &b), 3.0);
+        assert_eq!(mixed.distance(&a, &c), 1.0);
+        assert_eq!(mixed.distance(&b, &c), 10f64.sqrt());
+        assert_eq!(mixed.distance(&c, &c), 0.0);
+    }
+
+    #[test]
+    fn distance_matrix_is_symmetric_with_a_zero_diagonal() {
+        let data = vec![
+            Point::new(1.0, 1.0),
+            Point::new(1.0, 8.0),
+            Point::new(2.0, 5.0),
+            Point::new(6.0, 1.0),
+            Point::new(8.0, 6.0),
+        ];
+        let metric = Minkowski::new(1.0);
+        let matrix = distance_matrix(&data, &metric);
+
+        assert_eq!(matrix.len(), data.len());
+        for (i, row) in matrix.iter().enumerate() {
+            assert_eq!(row[i], 0.0);
+            for (j, &distance) in row.iter().enumerate() {
+                assert_eq!(distance, matrix[j][i]);
+            }
+        }
+        for (i, j) in [(0, 1), (1, 3), (2, 4)] {
+            assert_eq!(matrix[i][j], metric.distance(&data[i], &data[j]));
+        }
+        assert_eq!(matrix[0][4], 12.0);
+    }
+}
@@ -0,0 +1,61 @@
+use std::collections::HashMap;
+
+use crate::Point;
+
+/// Point indices per cluster label.
+pub fn group_by_label(data: &[Point], labels: &[usize]) -> HashMap<usize, Vec<usize>> {
+    assert_eq!(
+        data.len(),
+        labels.len(),
+        "expected one label per point, got {} labels for {} points",
+        labels.len(),
+        data.len()
+    );
+
+    let mut groups: HashMap<usize, Vec<usize>> = HashMap::new();
+    for (i, &label) in labels.iter().enumerate() {
+        groups.entry(label).or_default().push(i);
+    }
+    groups
+}
+
+/// Point indices per cluster label for clusterers that leave some points
+/// unassigned, such as DBSCAN; noise is grouped under `None`.
+pub fn group_by_optional_label(
+    data: &[Point],
+    labels: &[Option<usize>],
+) -> HashMap<Option<usize>, Vec<usize>> {
+    assert_eq!(
+        data.len(),
+        labels.len(),
+        "expected one label per point, got {} labels for {} points",
+        labels.len(),
+        data.len()
+    );
+
+    let mut groups: HashMap<Option<usize>, Vec<usize>> = HashMap::new();
+    for (i, &label) in labels.iter().enumerate() {
+        groups.entry(label).or_default().push(i);
+    }
+    groups
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_point_lands_in_exactly_one_bucket() {
+        let data: Vec<Point> = (0..7).map(|i| Point::new(i as f64, 0.0)).collect();
+
+        let mut grouped: Vec<usize> = group_by_label(&data, &[2, 0, 2, 1, 0, 2, 1]).into_values().flatten().collect();
+        grouped.sort_unstable();
+        assert_eq!(grouped, (0..7).collect::<Vec<_>>());
+
+        let groups = group_by_optional_label(&data, &[Some(0), None, Some(0), None, Some(1), Some(1), None]);
+        assert_eq!(groups[&None], vec![1, 3, 6]);
+        let mut grouped: Vec<usize> = groups.into_values().flatten().collect();
+        grouped.sort_unstable();
+        assert_eq!(grouped, (0..7).collect::<Vec<_>>());
+    }
+}
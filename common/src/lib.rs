@@ -0,0 +1,26 @@
+pub mod cli;
+pub mod clusterer;
+pub mod distance;
+pub mod labels;
+pub mod metrics;
+pub mod point;
+pub mod report;
+pub mod rules;
+pub mod transactions;
+
+pub use clusterer::{compare_clusterings, evaluate, Clusterer};
+pub use distance::{
+    distance_matrix, sort_distances, Distance, Euclidean, Manhattan, Metric, Minkowski,
+    MixedDistance,
+};
+pub use labels::{group_by_label, group_by_optional_label};
+pub use point::{assert_finite, first_non_finite, read_points, Point};
+pub use report::{ConsoleReporter, Reporter, Silent};
+pub use rules::{
+    evaluate_rules, filter_redundant, is_exact, minimal_antecedent_rules, rules_from_json,
+    rules_to_csv, rules_to_json, top_rules, value_score, ItemConstraints, JsonRule, RankBy, Rule,
+    RuleEvaluation,
+};
+pub use transactions::{
+    ancestors, augment_with_ancestors, dedup_transactions, read_transactions_json, ItemDictionary,
+};
@@ -0,0 +1,350 @@
+use std::collections::BTreeMap;
+
+use crate::Point;
+
+fn group_members(labels: &[usize]) -> BTreeMap<usize, Vec<usize>> {
+    let mut members: BTreeMap<usize, Vec<usize>> = BTreeMap::new();
+    for (i, &label) in labels.iter().enumerate() {
+        members.entry(label).or_default().push(i);
+    }
+    members
+}
+
+fn centroid(data: &[Point], indices: &[usize]) -> Point {
+    indices
+        .iter()
+        .skip(1)
+        .fold(data[indices[0]].clone(), |acc, &i| acc.add(&data[i]))
+        .scale(1.0 / indices.len() as f64)
+}
+
+/// Mean silhouette coefficient of a labelling. For each point, `a` is its
+/// mean distance to the rest of its cluster and `b` the smallest mean distance
+/// to another cluster; its silhouette is `(b - a) / max(a, b)`. Points in
+/// singleton clusters contribute 0, as does a labelling with a single cluster.
+pub fn silhouette_score(data: &[Point], labels: &[usize]) -> f64 {
+    silhouette_with(data.len(), labels, |i, j| data[i].distance(&data[j]))
+}
+
+/// `silhouette_score` over a precomputed pairwise distance matrix, for items
+/// that are not Euclidean points or were clustered under another metric.
+pub fn silhouette_from_distances(distances: &[Vec<f64>], labels: &[usize]) -> f64 {
+    silhouette_with(distances.len(), labels, |i, j| distances[i][j])
+}
+
+fn silhouette_with(n: usize, labels: &[usize], distance: impl Fn(usize, usize) -> f64) -> f64 {
+    if n == 0 {
+        return 0.0;
+    }
+
+    let members = group_members(labels);
+
+    if members.len() < 2 {
+        return 0.0;
+    }
+
+    let mean_distance = |i: usize, cluster: &[usize]| -> f64 {
+        let total: f64 = cluster.iter().map(|&j| distance(i, j)).sum();
+        let others = if labels[cluster[0]] == labels[i] {
+            cluster.len() - 1
+        } else {
+            cluster.len()
+        };
+        total / others as f64
+    };
+
+    let total: f64 = (0..n)
+        .map(|i| {
+            let own = &members[&labels[i]];
+            if own.len() == 1 {
+                return 0.0;
+            }
+
+            let a = mean_distance(i, own);
+            let b = members
+                .iter()
+                .filter(|(&label, _)| label != labels[i])
+                .map(|(_, cluster)| mean_distance(i, cluster))
+                .fold(f64::INFINITY, f64::min);
+
+            if a.max(b) > 0.0 {
+                (b - a) / a.max(b)
+            } else {
+                0.0
+            }
+        })
+        .sum();
+
+    total / n as f64
+}
+
+/// Davies-Bouldin index of a labelling (lower is better). For every cluster,
+/// `s_i` is the mean distance of its points to its centroid; the index is the
+/// mean over clusters of the worst ratio `(s_i + s_j) / d(c_i, c_j)` against
+/// any other cluster. Noise must be filtered out by the caller. A labelling
+/// with fewer than two clusters scores 0.
+pub fn davies_bouldin(data: &[Point], labels: &[usize]) -> f64 {
+    let members = group_members(labels);
+    if members.len() < 2 {
+        return 0.0;
+    }
+
+    let clusters: Vec<(Point, f64)> = members
+        .values()
+        .map(|indices| {
+            let centroid = centroid(data, indices);
+            let scatter = indices
+                .iter()
+                .map(|&i| data[i].distance(&centroid))
+                .sum::<f64>()
+                / indices.len() as f64;
+            (centroid, scatter)
+        })
+        .collect();
+
+    let total: f64 = clusters
+        .iter()
+        .enumerate()
+        .map(|(i, (centroid_i, scatter_i))| {
+            clusters
+                .iter()
+                .enumerate()
+                .filter(|(j, _)| *j != i)
+                .map(|(_, (centroid_j, scatter_j))| {
+                    (scatter_i + scatter_j) / centroid_i.distance(centroid_j)
+                })
+                .fold(0.0, f64::max)
+        })
+        .sum();
+
+    total / clusters.len() as f64
+}
+
+/// Total within-cluster sum of squared distances to the cluster centroids,
+/// comparable to k-means inertia. Points labelled `None` (noise) are skipped.
+pub fn cluster_sse(data: &[Point], labels: &[Option<usize>]) -> f64 {
+    let mut members: BTreeMap<usize, Vec<usize>> = BTreeMap::new();
+    for (i, label) in labels.iter().enumerate() {
+        if let Some(label) = label {
+            members.entry(*label).or_default().push(i);
+        }
+    }
+
+    members
+        .values()
+        .map(|indices| {
+            let centroid = centroid(data, indices);
+            indices
+                .iter()
+                .map(|&i| data[i].distance(&centroid).powi(2))
+                .sum::<f64>()
+        })
+        .sum()
+}
+
+/// Contingency counts between two labellings of the same points, keyed by
+/// `(predicted, truth)`.
+fn contingency<'a, P: Ord, T: Ord>(
+    predicted: &'a [P],
+    truth: &'a [T],
+) -> BTreeMap<(&'a P, &'a T), usize> {
+    assert_eq!(
+        predicted.len(),
+        truth.len(),
+        "predicted has {} labels but truth has {}",
+        predicted.len(),
+        truth.len()
+    );
+
+    let mut counts = BTreeMap::new();
+    for pair in predicted.iter().zip(truth) {
+        *counts.entry(pair).or_insert(0) += 1;
+    }
+    counts
+}
+
+/// Fraction of points whose true label is the majority true label of their
+/// predicted cluster. Labels may be any ordered type, so DBSCAN's
+/// `Option<usize>` labels can be passed directly with noise (`None`) scored
+/// as one more group. An empty labelling has purity 0.
+pub fn purity<P: Ord, T: Ord>(predicted: &[P], truth: &[T]) -> f64 {
+    if predicted.is_empty() {
+        return 0.0;
+    }
+
+    let mut majority: BTreeMap<&P, usize> = BTreeMap::new();
+    for ((cluster, _), count) in contingency(predicted, truth) {
+        let best = majority.entry(cluster).or_insert(0);
+        *best = (*best).max(count);
+    }
+
+    majority.values().sum::<usize>() as f64 / predicted.len() as f64
+}
+
+/// Adjusted Rand index (Hubert and Arabie, 1985): 1 for identical
+/// partitions, close to 0 for independent ones. Labels are compared as in
+/// `purity`. When both labellings are trivial (all points in one group, or
+/// fewer than two points) there is nothing to adjust for and the index is 1.
+pub fn adjusted_rand_index<P: Ord, T: Ord>(predicted: &[P], truth: &[T]) -> f64 {
+    let pairs = |count: usize| (count * count.saturating_sub(1)) as f64 / 2.0;
+
+    let table = contingency(predicted, truth);
+    let mut predicted_sizes: BTreeMap<&P, usize> = BTreeMap::new();
+    let mut truth_sizes: BTreeMap<&T, usize> = BTreeMap::new();
+    for (&(cluster, class), &count) in &table {
+        *predicted_sizes.entry(cluster).or_insert(0) += count;
+        *truth_sizes.entry(class).or_insert(0) += count;
+    }
+
+    let index: f64 = table.values().map(|&count| pairs(count)).sum();
+    let predicted_pairs: f64 = predicted_sizes.values().map(|&count| pairs(count)).sum();
+    let truth_pairs: f64 = truth_sizes.values().map(|&count| pairs(count)).sum();
+
+    let expected = predicted_pairs * truth_pairs / pairs(predicted.len()).max(1.0);
+    let max_index = (predicted_pairs + truth_pairs) / 2.0;
+
+    if max_index == expected {
+        return 1.0;
+    }
+    (index - expected) / (max_index - expected)
+}
+
+/// Minkowski distance `(Σ|a_i - b_i|^p)^(1/p)`. `p = 2` is exactly
+/// `Point::distance` and `p = 1` is the Manhattan distance.
+pub fn minkowski(a: &Point, b: &Point, p: f64) -> f64 {
+    if p == 2.0 {
+        return a.distance(b);
+    }
+    a.check_dim(b);
+    let sum: f64 = a.coords.iter().zip(&b.coords).map(|(x, y)| (x - y).abs().powf(p)).sum();
+    if p == 1.0 {
+        sum
+    } else {
+        sum.powf(1.0 / p)
+    }
+}
+
+/// Canberra distance `Σ|a_i - b_i| / (|a_i| + |b_i|)`. Coordinates where both
+/// points are zero contribute nothing instead of dividing by zero.
+pub fn canberra(a: &Point, b: &Point) -> f64 {
+    a.check_dim(b);
+    a.coords
+        .iter()
+        .zip(&b.coords)
+        .map(|(x, y)| {
+            let denominator = x.abs() + y.abs();
+            if denominator == 0.0 {
+                0.0
+            } else {
+                (x - y).abs() / denominator
+            }
+        })
+        .sum()
+}
+
+/// Bray-Curtis dissimilarity `Σ|a_i - b_i| / Σ(a_i + b_i)` for non-negative
+/// compositional data. Two all-zero points are at distance 0.
+pub fn bray_curtis(a: &Point, b: &Point) -> f64 {
+    a.check_dim(b);
+    let difference: f64 = a.coords.iter().zip(&b.coords).map(|(x, y)| (x - y).abs()).sum();
+    let total: f64 = a.coords.iter().zip(&b.coords).map(|(x, y)| x + y).sum();
+    if total == 0.0 {
+        0.0
+    } else {
+        difference / total
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sse_of_a_tight_cluster_is_small_and_skips_noise() {
+        let data = vec![
+            Point::new(0.0, 0.0),
+            Point::new(0.1, 0.0),
+            Point::new(0.0, 0.1),
+            Point::new(0.1, 0.1),
+            Point::new(50.0, 50.0),
+        ];
+        let labels = [Some(0), Some(0), Some(0), Some(0), None];
+
+        // Each corner is 0.05² + 0.05² = 0.005 from the centroid (0.05, 0.05).
+        assert!((cluster_sse(&data, &labels) - 0.02).abs() < 1e-12);
+    }
+
+    /// Two tight groups 100 apart, labelled 0 and 1.
+    fn two_blobs() -> (Vec<Point>, Vec<usize>) {
+        let data = vec![
+            Point::new(0.0, 0.0),
+            Point::new(0.2, 0.1),
+            Point::new(0.1, 0.3),
+            Point::new(100.0, 100.0),
+            Point::new(100.1, 100.2),
+            Point::new(99.8, 100.1),
+        ];
+        (data, vec![0, 0, 0, 1, 1, 1])
+    }
+
+    #[test]
+    fn well_separated_blobs_have_a_silhouette_near_one() {
+        let (data, labels) = two_blobs();
+        let score = silhouette_score(&data, &labels);
+        assert!(score > 0.99 && score <= 1.0, "silhouette {}", score);
+    }
+
+    #[test]
+    fn tight_far_apart_clusters_have_a_small_davies_bouldin_index() {
+        let (data, labels) = two_blobs();
+        let index = davies_bouldin(&data, &labels);
+        assert!((0.0..0.01).contains(&index), "Davies-Bouldin {}", index);
+    }
+
+    #[test]
+    fn minkowski_with_p_two_is_the_euclidean_distance() {
+        // Pseudo-random coordinates in [-5, 5).
+        let coord = |i: usize| ((i * 7919 + 13) % 1000) as f64 / 100.0 - 5.0;
+        for i in 0..20 {
+            let a = Point::from_slice(&[coord(4 * i), coord(4 * i + 1), coord(4 * i + 2)]);
+            let b = Point::from_slice(&[coord(4 * i + 3), coord(4 * i + 5), coord(4 * i + 7)]);
+            assert!((minkowski(&a, &b, 2.0) - a.distance(&b)).abs() < 1e-12);
+        }
+
+        let (a, b) = (Point::new(0.0, 0.0), Point::new(3.0, 4.0));
+        assert_eq!(minkowski(&a, &b, 1.0), 7.0);
+        assert!((minkowski(&a, &b, 3.0) - 91f64.cbrt()).abs() < 1e-12);
+    }
+
+    #[test]
+    fn identical_labellings_score_one_and_random_ones_near_zero() {
+        let truth: Vec<usize> = (0..600).map(|i| i % 3).collect();
+        let renamed: Vec<char> = truth.iter().map(|&label| ['x', 'y', 'z'][label]).collect();
+        assert_eq!(purity(&renamed, &truth), 1.0);
+        assert_eq!(adjusted_rand_index(&renamed, &truth), 1.0);
+
+        // A linear congruential generator stands in for random labels.
+        let mut state: u64 = 12345;
+        let random: Vec<u64> = (0..truth.len())
+            .map(|_| {
+                state = state.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+                (state >> 33) % 3
+            })
+            .collect();
+        let ari = adjusted_rand_index(&random, &truth);
+        assert!(ari.abs() < 0.05, "ARI {}", ari);
+    }
+
+    #[test]
+    fn canberra_and_bray_curtis_match_hand_computations() {
+        let (a, b) = (Point::new(1.0, 0.0), Point::new(3.0, 1.0));
+        // 2 / 4 + 1 / 1 and (2 + 1) / (4 + 1).
+        assert_eq!(canberra(&a, &b), 1.5);
+        assert_eq!(bray_curtis(&a, &b), 0.6);
+
+        for point in [a, b, Point::new(0.0, 0.0)] {
+            assert_eq!(canberra(&point, &point), 0.0);
+            assert_eq!(bray_curtis(&point, &point), 0.0);
+        }
+    }
+}
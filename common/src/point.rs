@@ -0,0 +1,206 @@
+use std::io::{self, BufRead};
+
+use serde::{Deserialize, Serialize};
+
+/// A point in N-dimensional Euclidean space, shared by all clustering
+/// binaries. `Point::new` builds the common 2D case.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Point {
+    pub coords: Vec<f64>,
+}
+
+impl Point {
+    pub fn new(x: f64, y: f64) -> Self {
+        Point { coords: vec![x, y] }
+    }
+
+    pub fn from_coords(coords: Vec<f64>) -> Self {
+        Point { coords }
+    }
+
+    pub fn from_slice(coords: &[f64]) -> Self {
+        Point {
+            coords: coords.to_vec(),
+        }
+    }
+
+    pub fn zeros(dim: usize) -> Self {
+        Point { coords: vec![0.0; dim] }
+    }
+
+    pub fn dim(&self) -> usize {
+        self.coords.len()
+    }
+
+    pub fn x(&self) -> f64 {
+        self.coords[0]
+    }
+
+    pub fn y(&self) -> f64 {
+        self.coords[1]
+    }
+
+    /// Panics with a descriptive message if `other` has a different
+    /// dimensionality.
+    pub fn check_dim(&self, other: &Point) {
+        assert_eq!(
+            self.dim(),
+            other.dim(),
+            "dimension mismatch: {}-dimensional point combined with {}-dimensional point",
+            self.dim(),
+            other.dim()
+        );
+    }
+
+    pub fn distance(&self, other: &Point) -> f64 {
+        self.distance_sq(other).sqrt()
+    }
+
+    /// Squared Euclidean distance, for radius checks that can compare against
+    /// a squared threshold instead of taking a square root.
+    pub fn distance_sq(&self, other: &Point) -> f64 {
+        self.check_dim(other);
+        self.coords
+            .iter()
+            .zip(&other.coords)
+            .map(|(a, b)| (a - b) * (a - b))
+            .sum()
+    }
+
+    pub fn add(&self, other: &Point) -> Point {
+        self.check_dim(other);
+        Point {
+            coords: self.coords.iter().zip(&other.coords).map(|(a, b)| a + b).collect(),
+        }
+    }
+
+    pub fn scale(&self, factor: f64) -> Point {
+        Point {
+            coords: self.coords.iter().map(|c| c * factor).collect(),
+        }
+    }
+
+    /// Whether no coordinate is NaN or infinite.
+    pub fn is_finite(&self) -> bool {
+        self.coords.iter().all(|c| c.is_finite())
+    }
+}
+
+/// Index of the first point with a NaN or infinite coordinate. Such a point
+/// turns every distance it takes part in into NaN or infinity, so clustering
+/// input is checked up front instead of producing meaningless labels.
+pub fn first_non_finite(data: &[Point]) -> Option<usize> {
+    data.iter().position(|point| !point.is_finite())
+}
+
+/// Panics naming the offending point if any coordinate in `data` is NaN or
+/// infinite; see `first_non_finite`.
+pub fn assert_finite(data: &[Point]) {
+    if let Some(i) = first_non_finite(data) {
+        panic!("point {} has a non-finite coordinate: {:?}", i, data[i].coords);
+    }
+}
+
+impl From<(f64, f64)> for Point {
+    fn from((x, y): (f64, f64)) -> Self {
+        Point::new(x, y)
+    }
+}
+
+impl From<[f64; 2]> for Point {
+    fn from([x, y]: [f64; 2]) -> Self {
+        Point::new(x, y)
+    }
+}
+
+fn invalid_data(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+/// Reads one point per line from comma-separated coordinates. Blank lines are
+/// skipped, as is a leading header row (a first line with no numeric field).
+/// Every point must have the same number of coordinates; malformed numbers
+/// and ragged rows are reported as `InvalidData` errors with the line number.
+pub fn read_points<R: BufRead>(reader: R) -> io::Result<Vec<Point>> {
+    let mut points: Vec<Point> = Vec::new();
+    let mut seen_content = false;
+
+    for (line_idx, line) in reader.lines().enumerate() {
+        let line = line?;
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+        let is_first = !seen_content;
+        seen_content = true;
+
+        if is_first && fields.iter().all(|field| field.parse::<f64>().is_err()) {
+            continue;
+        }
+
+        let coords = fields
+            .iter()
+            .map(|field| {
+                field.parse::<f64>().map_err(|_| {
+                    invalid_data(format!("line {}: invalid number '{}'", line_idx + 1, field))
+                })
+            })
+            .collect::<io::Result<Vec<f64>>>()?;
+
+        if let Some(first) = points.first() {
+            if first.dim() != coords.len() {
+                return Err(invalid_data(format!(
+                    "line {}: expected {} coordinates, found {}",
+                    line_idx + 1,
+                    first.dim(),
+                    coords.len()
+                )));
+            }
+        }
+
+        points.push(Point::from_coords(coords));
+    }
+
+    Ok(points)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn distance_is_euclidean_in_any_dimension() {
+        assert_eq!(Point::new(0.0, 0.0).distance(&Point::new(3.0, 4.0)), 5.0);
+        assert_eq!(Point::new(1.0, 1.0).distance_sq(&Point::new(4.0, 5.0)), 25.0);
+        let a = Point::from_slice(&[1.0, 2.0, 3.0]);
+        assert_eq!(a.distance(&Point::from_slice(&[3.0, 5.0, 9.0])), 7.0);
+        assert_eq!(a.distance(&a), 0.0);
+    }
+
+    #[test]
+    fn reads_points_from_in_memory_csv() {
+        let csv = "x,y\n1.0, 2.0\n\n3.5,-4\n";
+        let points = read_points(csv.as_bytes()).unwrap();
+        assert_eq!(points, vec![Point::new(1.0, 2.0), Point::new(3.5, -4.0)]);
+
+        let err = read_points("1,2\n3,abc\n".as_bytes()).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert!(err.to_string().contains("line 2"), "{}", err);
+
+        let err = read_points("1,2\n3,4,5\n".as_bytes()).unwrap_err();
+        assert!(err.to_string().contains("expected 2 coordinates, found 3"), "{}", err);
+    }
+
+    #[test]
+    fn conversions_build_the_same_point() {
+        let expected = Point::from_coords(vec![3.0, 4.0]);
+        assert_eq!(Point::from((3.0, 4.0)), expected);
+        assert_eq!(Point::from([3.0, 4.0]), expected);
+        assert_eq!(Point::from_slice(&[3.0, 4.0]), expected);
+        assert_eq!(Point::new(3.0, 4.0), expected);
+        assert_eq!(Point::from_slice(&[1.0]).dim(), 1);
+        assert_eq!(Point::from((3.0, 4.0)).distance(&Point::from((0.0, 0.0))), 5.0);
+    }
+}
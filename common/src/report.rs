@@ -0,0 +1,47 @@
+use std::fmt;
+
+use crate::Point;
+
+/// Hooks for progress the algorithms would otherwise print. Every method
+/// defaults to doing nothing, so library callers stay silent unless they
+/// opt into `ConsoleReporter` or their own implementation.
+pub trait Reporter {
+    /// An FP-tree has been built from the transactions.
+    fn on_tree_built(&mut self, _tree: &dyn fmt::Display) {}
+
+    /// A k-means fit finished after `iterations` with these centroids.
+    fn on_converged(&mut self, _iterations: usize, _centroids: &[Point]) {}
+
+    /// FP-Growth stopped recursing at `max_depth` conditional trees, so some
+    /// long itemsets are missing from its result.
+    fn on_depth_limit(&mut self, _max_depth: usize) {}
+}
+
+/// Reports nothing; the default for every algorithm.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Silent;
+
+impl Reporter for Silent {}
+
+/// Prints every event to stdout in the format the binaries have always used.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ConsoleReporter;
+
+impl Reporter for ConsoleReporter {
+    fn on_tree_built(&mut self, tree: &dyn fmt::Display) {
+        println!("{}", tree);
+    }
+
+    fn on_converged(&mut self, iterations: usize, centroids: &[Point]) {
+        println!("Converged after {} iterations", iterations);
+        println!("Final centroids: {:?}", centroids);
+    }
+
+    fn on_depth_limit(&mut self, max_depth: usize) {
+        println!(
+            "Stopped at recursion depth {}; itemsets with more than {} items were omitted",
+            max_depth,
+            max_depth + 1
+        );
+    }
+}
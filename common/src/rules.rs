@@ -0,0 +1,411 @@
+use std::collections::{HashMap, HashSet};
+use std::hash::Hash;
+
+use serde::{Deserialize, Serialize};
+
+use crate::transactions::ancestors;
+
+/// An association rule `antecedent => consequent`, shared by Apriori and
+/// FP-Growth. `support` is relative to the number of transactions;
+/// `conviction` is infinite for exact rules, which are also flagged `exact`.
+/// `value_score` is 0 unless item values were given to rule generation.
+/// With `negated_consequent` the rule reads `antecedent => not consequent`
+/// and every measure refers to the absence of the consequent.
+/// `antecedent_support` and `consequent_support` are absolute transaction
+/// counts, so the measures can be recomputed without the support table.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Rule<I = char> {
+    pub antecedent: Vec<I>,
+    pub consequent: Vec<I>,
+    pub support: f64,
+    pub antecedent_support: usize,
+    pub consequent_support: usize,
+    pub confidence: f64,
+    pub lift: f64,
+    pub leverage: f64,
+    pub conviction: f64,
+    pub exact: bool,
+    pub value_score: f64,
+    pub negated_consequent: bool,
+}
+
+impl<I> Rule<I> {
+    /// The same rule over other items, e.g. to map dictionary ids back to
+    /// the items they stand for. The measures are kept as they are.
+    pub fn map_items<J>(self, mut f: impl FnMut(I) -> J) -> Rule<J> {
+        Rule {
+            antecedent: self.antecedent.into_iter().map(&mut f).collect(),
+            consequent: self.consequent.into_iter().map(&mut f).collect(),
+            support: self.support,
+            antecedent_support: self.antecedent_support,
+            consequent_support: self.consequent_support,
+            confidence: self.confidence,
+            lift: self.lift,
+            leverage: self.leverage,
+            conviction: self.conviction,
+            exact: self.exact,
+            value_score: self.value_score,
+            negated_consequent: self.negated_consequent,
+        }
+    }
+}
+
+/// The sum of the values of a rule's items times its `support`, so frequent
+/// rules over cheap items can rank below rarer ones over expensive items.
+/// Items missing from `item_values` are worth 0, as is every rule when no
+/// values are given.
+pub fn value_score<I: Eq + Hash>(
+    antecedent: &[I],
+    consequent: &[I],
+    support: f64,
+    item_values: Option<&HashMap<I, f64>>,
+) -> f64 {
+    let Some(item_values) = item_values else {
+        return 0.0;
+    };
+    let total: f64 = antecedent
+        .iter()
+        .chain(consequent)
+        .map(|item| item_values.get(item).copied().unwrap_or(0.0))
+        .sum();
+    total * support
+}
+
+/// The measure `top_rules` orders by.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RankBy {
+    Support,
+    Confidence,
+    Lift,
+    Value,
+}
+
+/// The `n` highest-ranked rules by `by`, best first. Rules that tie keep
+/// their original order.
+pub fn top_rules<I: Clone>(rules: &[Rule<I>], n: usize, by: RankBy) -> Vec<Rule<I>> {
+    let key = |rule: &Rule<I>| match by {
+        RankBy::Support => rule.support,
+        RankBy::Confidence => rule.confidence,
+        RankBy::Lift => rule.lift,
+        RankBy::Value => rule.value_score,
+    };
+
+    let mut ranked = rules.to_vec();
+    ranked.sort_by(|a, b| key(b).total_cmp(&key(a)));
+    ranked.truncate(n);
+    ranked
+}
+
+/// A rule's support and confidence recomputed on other transactions.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RuleEvaluation {
+    pub support: f64,
+    pub confidence: f64,
+}
+
+/// Recomputes every rule's support and confidence on `transactions`, e.g. a
+/// held-out set, so rules whose confidence collapses on fresh data stand out.
+/// Items are matched by set membership. A rule whose antecedent never occurs
+/// gets confidence 0, and every measure is 0 on an empty transaction set.
+pub fn evaluate_rules<I: PartialEq>(rules: &[Rule<I>], transactions: &[Vec<I>]) -> Vec<RuleEvaluation> {
+    let contains_all = |transaction: &[I], items: &[I]| items.iter().all(|item| transaction.contains(item));
+    let n = transactions.len().max(1) as f64;
+
+    rules
+        .iter()
+        .map(|rule| {
+            let mut antecedent_count = 0;
+            let mut rule_count = 0;
+            for transaction in transactions {
+                if contains_all(transaction, &rule.antecedent) {
+                    antecedent_count += 1;
+                    if contains_all(transaction, &rule.consequent) {
+                        rule_count += 1;
+                    }
+                }
+            }
+
+            RuleEvaluation {
+                support: rule_count as f64 / n,
+                confidence: if antecedent_count == 0 {
+                    0.0
+                } else {
+                    rule_count as f64 / antecedent_count as f64
+                },
+            }
+        })
+        .collect()
+}
+
+/// Drops every rule for which a more general rule exists: one with the same
+/// consequent, an antecedent that is a proper subset of its antecedent, and
+/// at least its confidence. The surviving rules keep their original order.
+pub fn filter_redundant<I: PartialEq>(rules: Vec<Rule<I>>) -> Vec<Rule<I>> {
+    let same_items = |a: &[I], b: &[I]| a.len() == b.len() && a.iter().all(|item| b.contains(item));
+
+    let redundant: Vec<bool> = rules
+        .iter()
+        .map(|rule| {
+            rules.iter().any(|general| {
+                general.antecedent.len() < rule.antecedent.len()
+                    && general.antecedent.iter().all(|item| rule.antecedent.contains(item))
+                    && same_items(&general.consequent, &rule.consequent)
+                    && general.confidence >= rule.confidence
+            })
+        })
+        .collect();
+
+    rules
+        .into_iter()
+        .zip(redundant)
+        .filter(|(_, redundant)| !redundant)
+        .map(|(rule, _)| rule)
+        .collect()
+}
+
+/// Keeps, for every consequent, only the rules with a minimal antecedent:
+/// a rule is dropped when another rule in `rules` has the same consequent and
+/// an antecedent that is a proper subset of its own, whatever the two
+/// confidences. Unlike `filter_redundant`, a more specific rule never survives
+/// by being more confident. The surviving rules keep their original order.
+pub fn minimal_antecedent_rules<I: PartialEq>(rules: Vec<Rule<I>>) -> Vec<Rule<I>> {
+    let same_items = |a: &[I], b: &[I]| a.len() == b.len() && a.iter().all(|item| b.contains(item));
+
+    let dominated: Vec<bool> = rules
+        .iter()
+        .map(|rule| {
+            rules.iter().any(|smaller| {
+                smaller.antecedent.len() < rule.antecedent.len()
+                    && smaller.antecedent.iter().all(|item| rule.antecedent.contains(item))
+                    && same_items(&smaller.consequent, &rule.consequent)
+            })
+        })
+        .collect();
+
+    rules
+        .into_iter()
+        .zip(dominated)
+        .filter(|(_, dominated)| !dominated)
+        .map(|(rule, _)| rule)
+        .collect()
+}
+
+/// How far below 1.0 a confidence may fall and still count as an exact
+/// implication, absorbing rounding in the support ratio.
+pub const EXACT_CONFIDENCE_TOLERANCE: f64 = 1e-9;
+
+/// Whether a rule with this confidence holds in every transaction that
+/// contains its antecedent.
+pub fn is_exact(confidence: f64) -> bool {
+    (confidence - 1.0).abs() <= EXACT_CONFIDENCE_TOLERANCE
+}
+
+/// Item restrictions applied while generating rules, e.g. to ask "what
+/// predicts item X". `None` leaves that side of the rule unrestricted.
+#[derive(Debug, Clone)]
+pub struct ItemConstraints<I = char> {
+    /// Only rules whose consequent is a subset of this set are emitted.
+    pub require_consequent: Option<HashSet<I>>,
+    /// Rules with any of these items in the antecedent are dropped.
+    pub forbid_antecedent: Option<HashSet<I>>,
+    /// A child-to-parent taxonomy. Rules relating an item to one of its own
+    /// ancestors, such as "milk => dairy", are dropped as trivially true.
+    pub item_parent: Option<HashMap<I, I>>,
+}
+
+impl<I> Default for ItemConstraints<I> {
+    fn default() -> Self {
+        ItemConstraints {
+            require_consequent: None,
+            forbid_antecedent: None,
+            item_parent: None,
+        }
+    }
+}
+
+impl<I: Eq + Hash> ItemConstraints<I> {
+    /// Whether `antecedent => consequent` satisfies every restriction.
+    pub fn allows(&self, antecedent: &[I], consequent: &[I]) -> bool {
+        let consequent_ok = self
+            .require_consequent
+            .as_ref()
+            .is_none_or(|required| consequent.iter().all(|item| required.contains(item)));
+        let antecedent_ok = self
+            .forbid_antecedent
+            .as_ref()
+            .is_none_or(|forbidden| !antecedent.iter().any(|item| forbidden.contains(item)));
+        let taxonomy_ok = self.item_parent.as_ref().is_none_or(|item_parent| {
+            let related = |a: &I, b: &I| ancestors(a, item_parent).any(|ancestor| ancestor == b);
+            !antecedent
+                .iter()
+                .any(|a| consequent.iter().any(|c| related(a, c) || related(c, a)))
+        });
+        consequent_ok && antecedent_ok && taxonomy_ok
+    }
+}
+
+/// The JSON shape of a rule. Items are written as strings so rules over any
+/// item type can be consumed the same way.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct JsonRule {
+    pub antecedent: Vec<String>,
+    pub consequent: Vec<String>,
+    pub support: f64,
+    pub confidence: f64,
+    pub lift: f64,
+}
+
+impl<I: ToString> From<&Rule<I>> for JsonRule {
+    fn from(rule: &Rule<I>) -> Self {
+        JsonRule {
+            antecedent: rule.antecedent.iter().map(ToString::to_string).collect(),
+            consequent: rule.consequent.iter().map(ToString::to_string).collect(),
+            support: rule.support,
+            confidence: rule.confidence,
+            lift: rule.lift,
+        }
+    }
+}
+
+/// Serializes rules as a JSON array of objects with `antecedent`,
+/// `consequent`, `support`, `confidence` and `lift` fields.
+pub fn rules_to_json<I: ToString>(rules: &[Rule<I>]) -> String {
+    let records: Vec<JsonRule> = rules.iter().map(JsonRule::from).collect();
+    serde_json::to_string(&records).expect("rule records always serialize")
+}
+
+/// Serializes rules as CSV for spreadsheets: a header row
+/// `antecedent,consequent,support,confidence,lift`, then one row per rule.
+/// Itemsets are `|`-joined and measures have 4 decimal places. Fields
+/// containing a comma, quote or line break are quoted, with inner quotes
+/// doubled.
+pub fn rules_to_csv<I: ToString>(rules: &[Rule<I>]) -> String {
+    fn field(value: String) -> String {
+        if value.contains([',', '"', '\n', '\r']) {
+            format!("\"{}\"", value.replace('"', "\"\""))
+        } else {
+            value
+        }
+    }
+    let itemset = |items: &[I]| field(items.iter().map(ToString::to_string).collect::<Vec<_>>().join("|"));
+
+    let mut csv = String::from("antecedent,consequent,support,confidence,lift\n");
+    for rule in rules {
+        csv.push_str(&format!(
+            "{},{},{:.4},{:.4},{:.4}\n",
+            itemset(&rule.antecedent),
+            itemset(&rule.consequent),
+            rule.support,
+            rule.confidence,
+            rule.lift
+        ));
+    }
+    csv
+}
+
+/// Parses the output of `rules_to_json` back into rule records.
+pub fn rules_from_json(s: &str) -> Result<Vec<JsonRule>, serde_json::Error> {
+    serde_json::from_str(s)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(antecedent: &[char], consequent: &[char], support: f64, confidence: f64) -> Rule {
+        Rule {
+            antecedent: antecedent.to_vec(),
+            consequent: consequent.to_vec(),
+            support,
+            antecedent_support: 0,
+            consequent_support: 0,
+            confidence,
+            lift: 1.5,
+            leverage: 0.0,
+            conviction: 1.0,
+            exact: is_exact(confidence),
+            value_score: 0.0,
+            negated_consequent: false,
+        }
+    }
+
+    #[test]
+    fn json_round_trips_to_equal_rules() {
+        let rules = vec![rule(&['a'], &['b'], 0.5, 0.75), rule(&['a', 'c'], &['b', 'd'], 0.25, 1.0)];
+
+        let parsed = rules_from_json(&rules_to_json(&rules)).unwrap();
+        assert_eq!(parsed, rules.iter().map(JsonRule::from).collect::<Vec<_>>());
+        assert_eq!(parsed[1].antecedent, vec!["a", "c"]);
+        assert_eq!(parsed[1].confidence, 1.0);
+    }
+
+    #[test]
+    fn a_specific_rule_no_more_confident_than_a_general_one_is_dropped() {
+        let general = rule(&['a'], &['c'], 0.4, 0.8);
+        let specific = rule(&['a', 'b'], &['c'], 0.2, 0.8);
+        let more_confident = rule(&['a', 'd'], &['c'], 0.2, 0.9);
+
+        let kept = filter_redundant(vec![specific, general.clone(), more_confident.clone()]);
+        assert_eq!(kept, vec![general, more_confident]);
+    }
+
+    #[test]
+    fn only_the_minimal_antecedent_survives_per_consequent() {
+        let minimal = rule(&['a'], &['c'], 0.4, 0.6);
+        let larger = rule(&['a', 'b'], &['c'], 0.2, 1.0);
+        let other_consequent = rule(&['a', 'b'], &['d'], 0.2, 1.0);
+
+        let kept = minimal_antecedent_rules(vec![larger, minimal.clone(), other_consequent.clone()]);
+        assert_eq!(kept, vec![minimal, other_consequent]);
+    }
+
+    #[test]
+    fn an_exact_rule_scores_lower_on_held_out_data_that_violates_it() {
+        let exact = rule(&['a'], &['b'], 0.5, 1.0);
+        let held_out = vec![vec!['a', 'b'], vec!['a'], vec!['a', 'c'], vec!['b']];
+
+        let evaluation = evaluate_rules(&[exact], &held_out)[0];
+        assert_eq!(evaluation, RuleEvaluation { support: 0.25, confidence: 1.0 / 3.0 });
+
+        let unseen = evaluate_rules(&[rule(&['z'], &['b'], 0.5, 1.0)], &held_out)[0];
+        assert_eq!(unseen, RuleEvaluation { support: 0.0, confidence: 0.0 });
+    }
+
+    #[test]
+    fn a_rare_valuable_rule_outranks_a_frequent_cheap_one_by_value() {
+        let values = HashMap::from([('a', 1.0), ('b', 1.0), ('x', 50.0)]);
+        let mut frequent = rule(&['a'], &['b'], 0.6, 0.9);
+        frequent.value_score = value_score(&frequent.antecedent, &frequent.consequent, frequent.support, Some(&values));
+        let mut valuable = rule(&['a'], &['x'], 0.1, 0.9);
+        valuable.value_score = value_score(&valuable.antecedent, &valuable.consequent, valuable.support, Some(&values));
+
+        // 2 * 0.6 against 51 * 0.1.
+        assert!((frequent.value_score - 1.2).abs() < 1e-12);
+        assert!((valuable.value_score - 5.1).abs() < 1e-12);
+        let rules = [frequent.clone(), valuable.clone()];
+        assert_eq!(top_rules(&rules, 1, RankBy::Support), vec![frequent]);
+        assert_eq!(top_rules(&rules, 1, RankBy::Value), vec![valuable]);
+        assert_eq!(value_score(&['a'], &['x'], 0.1, None), 0.0);
+    }
+
+    #[test]
+    fn csv_has_a_header_and_one_line_per_rule() {
+        let rules = vec![rule(&['a'], &['b'], 0.5, 0.75), rule(&['a', 'c'], &['b', 'd'], 0.25, 1.0)];
+
+        let csv = rules_to_csv(&rules);
+        assert_eq!(csv.lines().count(), rules.len() + 1);
+        assert_eq!(csv.lines().next(), Some("antecedent,consequent,support,confidence,lift"));
+        assert_eq!(csv.lines().nth(2), Some("a|c,b|d,0.2500,1.0000,1.5000"));
+    }
+
+    #[test]
+    fn csv_quotes_items_containing_the_delimiter() {
+        let salt = rule(&['a'], &['b'], 0.5, 1.0).map_items(|item| match item {
+            'a' => "salt, \"coarse\"".to_string(),
+            other => other.to_string(),
+        });
+
+        let csv = rules_to_csv(&[salt]);
+        assert_eq!(csv.lines().nth(1), Some("\"salt, \"\"coarse\"\"\",b,0.5000,1.0000,1.5000"));
+    }
+}
@@ -0,0 +1,121 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+
+/// Parses transactions from a JSON array of arrays of strings, e.g.
+/// `[["a","b"],["b","c"]]`. Items are kept as strings so the same file can
+/// drive both Apriori and FP-Growth; single-character items can be narrowed
+/// to `char` by the caller.
+pub fn read_transactions_json(s: &str) -> Result<Vec<Vec<String>>, serde_json::Error> {
+    serde_json::from_str(s)
+}
+
+/// Sorts each transaction and removes repeated items. Both miners treat a
+/// transaction as a set of items, so an item occurring several times in one
+/// basket still contributes a support of 1.
+pub fn dedup_transactions<I: Ord>(transactions: &mut [Vec<I>]) {
+    for transaction in transactions.iter_mut() {
+        transaction.sort();
+        transaction.dedup();
+    }
+}
+
+/// The parent, grandparent and so on of `item` in a taxonomy given as a
+/// child-to-parent map, nearest first. A cycle in the map is walked at most
+/// once around.
+pub fn ancestors<'a, I: Eq + Hash>(
+    item: &I,
+    item_parent: &'a HashMap<I, I>,
+) -> impl Iterator<Item = &'a I> {
+    std::iter::successors(item_parent.get(item), |parent| item_parent.get(*parent))
+        .take(item_parent.len())
+}
+
+/// Adds every ancestor of every item to its transaction, then sorts and
+/// deduplicates it as `dedup_transactions` does, so categories can be mined
+/// alongside the items they contain (generalized association rules).
+pub fn augment_with_ancestors<I: Ord + Hash + Clone>(
+    transactions: &mut [Vec<I>],
+    item_parent: &HashMap<I, I>,
+) {
+    for transaction in transactions.iter_mut() {
+        let inherited: Vec<I> = transaction
+            .iter()
+            .flat_map(|item| ancestors(item, item_parent))
+            .cloned()
+            .collect();
+        transaction.extend(inherited);
+    }
+    dedup_transactions(transactions);
+}
+
+/// Dense `u32` ids for the distinct items of a set of transactions, so a
+/// miner can hash and compare integers in its inner loops and map the results
+/// back at the end. Ids are assigned in item order, so sorting ids sorts the
+/// items they stand for.
+#[derive(Debug, Clone)]
+pub struct ItemDictionary<I> {
+    ids: HashMap<I, u32>,
+    items: Vec<I>,
+}
+
+impl<I: Ord + Hash + Clone> ItemDictionary<I> {
+    pub fn from_transactions<'a, T>(transactions: impl IntoIterator<Item = T>) -> Self
+    where
+        T: IntoIterator<Item = &'a I>,
+        I: 'a,
+    {
+        let mut items: Vec<I> = transactions.into_iter().flatten().cloned().collect();
+        items.sort();
+        items.dedup();
+        let ids = items.iter().enumerate().map(|(id, item)| (item.clone(), id as u32)).collect();
+        ItemDictionary { ids, items }
+    }
+
+    pub fn id(&self, item: &I) -> Option<u32> {
+        self.ids.get(item).copied()
+    }
+
+    /// Panics if `id` was not assigned by this dictionary.
+    pub fn item(&self, id: u32) -> &I {
+        &self.items[id as usize]
+    }
+
+    /// Maps every item to its id. Panics on an item the dictionary was not
+    /// built from.
+    pub fn encode<'a, C>(&self, items: impl IntoIterator<Item = &'a I>) -> C
+    where
+        C: FromIterator<u32>,
+        I: 'a,
+    {
+        items
+            .into_iter()
+            .map(|item| self.id(item).expect("item missing from the dictionary"))
+            .collect()
+    }
+
+    pub fn decode(&self, ids: &[u32]) -> Vec<I> {
+        ids.iter().map(|&id| self.item(id).clone()).collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_nested_arrays_of_items() {
+        let transactions = read_transactions_json(r#"[["milk", "bread"], [], ["eggs"]]"#).unwrap();
+        assert_eq!(transactions, vec![vec!["milk".to_string(), "bread".to_string()], vec![], vec!["eggs".to_string()]]);
+
+        assert!(read_transactions_json(r#"[["milk", 1]]"#).is_err());
+        assert!(read_transactions_json(r#"["milk"]"#).is_err());
+    }
+}
@@ -0,0 +1,66 @@
+use crate::{Point, PointType, DBSCAN};
+
+/// Generalized DBSCAN: neighborhoods are defined by an arbitrary symmetric
+/// predicate and core points by an arbitrary test on the neighborhood, so
+/// points can be clustered on more than distance (e.g. "same category and
+/// within eps"). `DBSCAN::fit` is this with the eps/min_points predicates.
+pub struct Gdbscan<N, C> {
+    neighbor_predicate: N,
+    min_cardinality: C,
+    /// Leave non-core points as noise instead of attaching them to a cluster
+    /// as border points (DBSCAN*).
+    pub treat_border_as_noise: bool,
+}
+
+impl<N, C> Gdbscan<N, C>
+where
+    N: Fn(&Point, &Point) -> bool,
+    C: Fn(&[usize]) -> bool,
+{
+    pub fn new(neighbor_predicate: N, min_cardinality: C) -> Self {
+        Gdbscan {
+            neighbor_predicate,
+            min_cardinality,
+            treat_border_as_noise: false,
+        }
+    }
+
+    /// Indices of all other points the predicate accepts as neighbors of
+    /// `point_idx`.
+    pub fn region_query(&self, data: &[Point], point_idx: usize) -> Vec<usize> {
+        let point = &data[point_idx];
+        data.iter()
+            .enumerate()
+            .filter(|(i, p)| *i != point_idx && (self.neighbor_predicate)(point, p))
+            .map(|(i, _)| i)
+            .collect()
+    }
+
+    pub fn fit(&self, data: &[Point]) -> (Vec<Option<usize>>, Vec<PointType>) {
+        DBSCAN::fit_with(data.len(), self.treat_border_as_noise, |i| {
+            let neighbors = self.region_query(data, i);
+            (self.min_cardinality)(&neighbors).then_some(neighbors)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn eps_and_category_predicates_combine() {
+        // x, y and a category; the categories alternate along a line.
+        let data: Vec<Point> = (0..4)
+            .map(|i| Point::from_slice(&[i as f64 * 0.5, 0.0, (i % 2) as f64]))
+            .collect();
+        let within_eps = |a: &Point, b: &Point| Point::new(a.x(), a.y()).distance(&Point::new(b.x(), b.y())) <= 1.0;
+
+        let (by_distance, _) = Gdbscan::new(within_eps, |neighbors: &[usize]| !neighbors.is_empty()).fit(&data);
+        assert_eq!(by_distance, vec![Some(1); 4]);
+
+        let same_category = |a: &Point, b: &Point| a.coords[2] == b.coords[2] && within_eps(a, b);
+        let (clusters, _) = Gdbscan::new(same_category, |neighbors: &[usize]| !neighbors.is_empty()).fit(&data);
+        assert_eq!(clusters, vec![Some(1), Some(2), Some(1), Some(2)]);
+    }
+}
@@ -0,0 +1,52 @@
+use common::sort_distances;
+
+use crate::Point;
+
+/// Distance from every point to its `min_points`-th nearest other point, the
+/// density estimate HDBSCAN builds on. Infinite when fewer than `min_points`
+/// other points exist; zero when `min_points` is zero.
+pub fn core_distances(data: &[Point], min_points: usize) -> Vec<f64> {
+    (0..data.len())
+        .map(|i| {
+            if min_points == 0 {
+                return 0.0;
+            }
+            let mut distances: Vec<f64> = data
+                .iter()
+                .enumerate()
+                .filter(|&(j, _)| j != i)
+                .map(|(_, point)| point.distance(&data[i]))
+                .collect();
+            sort_distances(&mut distances);
+            distances.get(min_points - 1).copied().unwrap_or(f64::INFINITY)
+        })
+        .collect()
+}
+
+/// `max(core[i], core[j], d(i, j))`: points in dense regions keep their plain
+/// distance, while sparse points are pushed apart to their core distance.
+pub fn mutual_reachability(data: &[Point], core: &[f64], i: usize, j: usize) -> f64 {
+    core[i].max(core[j]).max(data[i].distance(&data[j]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mutual_reachability_is_plain_when_dense_and_core_when_isolated() {
+        let data = vec![
+            Point::new(0.0, 0.0),
+            Point::new(0.0, 1.0),
+            Point::new(1.0, 0.0),
+            Point::new(1.0, 1.0),
+            Point::new(10.0, 10.0),
+        ];
+        let core = core_distances(&data, 2);
+        assert_eq!(core[..4], [1.0; 4]);
+
+        assert_eq!(mutual_reachability(&data, &core, 0, 3), data[0].distance(&data[3]));
+        assert!(data[4].distance(&data[3]) < core[4]);
+        assert_eq!(mutual_reachability(&data, &core, 4, 3), core[4]);
+    }
+}
@@ -1,28 +1,46 @@
-use std::collections::{HashMap, HashSet, VecDeque};
+mod gdbscan;
+mod hdbscan;
+mod optics;
 
-#[derive(Debug, Clone, Copy, PartialEq)]
-struct Point {
-    x: f64,
-    y: f64,
-}
-
-impl Point {
-    fn new(x: f64, y: f64) -> Self {
-        Point { x, y }
-    }
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::fs::File;
+use std::io::{self, BufReader};
 
-    fn distance(&self, other: &Point) -> f64 {
-        let dx = self.x - other.x;
-        let dy = self.y - other.y;
-        (dx * dx + dy * dy).sqrt()
-    }
-}
+use common::cli::{self, Args};
+use common::metrics::{adjusted_rand_index, cluster_sse, davies_bouldin};
+use common::{
+    assert_finite, compare_clusterings, evaluate, group_by_optional_label, read_points,
+    sort_distances, Clusterer, Distance, Metric, Minkowski, MixedDistance, Point,
+};
+use gdbscan::Gdbscan;
+use hdbscan::{core_distances, mutual_reachability};
+use optics::{extract_dbscan, Optics};
 
+#[allow(clippy::upper_case_acronyms)]
 struct DBSCAN {
     eps: f64,
     min_points: usize, 
+    convention: MinPtsConvention,
+    /// Distance used for eps-neighborhoods; Euclidean (`p = 2`) by default.
+    metric: Metric,
+    /// DBSCAN*: only core points join clusters and every non-core point is
+    /// noise, so the result no longer depends on the order border points are
+    /// reached in. Off by default.
+    treat_border_as_noise: bool,
+}
+
+/// Whether `min_points` counts the query point itself. The literature uses
+/// both; `ExcludingSelf` is the historical behaviour of this implementation.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+enum MinPtsConvention {
+    IncludingSelf,
+    #[default]
+    ExcludingSelf,
 }
 
+/// Role of a point after `fit`. `Unclassified` is only the working state
+/// while clusters are expanded: every point leaves as `Core` or `Border` with
+/// a cluster id, or as `Noise` without one.
 #[derive(Debug, Clone, Copy, PartialEq)]
 enum PointType {
     Core,      
@@ -31,39 +49,230 @@ enum PointType {
     Unclassified, 
 }
 
+/// Flat DBSCAN output in the scikit-learn convention: noise is labelled `-1`
+/// and clusters are numbered from `0`.
+#[derive(Debug, Clone)]
+struct DbscanResult {
+    labels: Vec<i64>,
+    point_types: Vec<PointType>,
+    cluster_sizes: HashMap<i64, usize>,
+}
+
+impl DbscanResult {
+    /// Converts `fit` output (1-based ids, `None` for noise) into the flat
+    /// labelling.
+    fn from_fit(clusters: Vec<Option<usize>>, point_types: Vec<PointType>) -> Self {
+        let labels: Vec<i64> = clusters
+            .iter()
+            .map(|cluster| match cluster {
+                Some(id) => *id as i64 - 1,
+                None => -1,
+            })
+            .collect();
+
+        let mut cluster_sizes = HashMap::new();
+        for &label in &labels {
+            *cluster_sizes.entry(label).or_insert(0) += 1;
+        }
+
+        DbscanResult {
+            labels,
+            point_types,
+            cluster_sizes,
+        }
+    }
+
+    fn cluster_count(&self) -> usize {
+        self.cluster_sizes.keys().filter(|&&label| label >= 0).count()
+    }
+
+    fn noise_count(&self) -> usize {
+        self.cluster_sizes.get(&-1).copied().unwrap_or(0)
+    }
+}
+
+/// Location and 2D extent of one cluster.
+#[derive(Debug, Clone, PartialEq)]
+struct ClusterSummary {
+    size: usize,
+    centroid: Point,
+    min_x: f64,
+    max_x: f64,
+    min_y: f64,
+    max_y: f64,
+}
+
+/// Per-cluster size, centroid and bounding box for `fit` labels, keyed by
+/// cluster id. Noise points are excluded. The box spans the first two
+/// coordinates; panics if a clustered point has fewer than two.
+fn cluster_summaries(data: &[Point], labels: &[Option<usize>]) -> HashMap<usize, ClusterSummary> {
+    let mut summaries: HashMap<usize, ClusterSummary> = HashMap::new();
+
+    for (point, label) in data.iter().zip(labels) {
+        let Some(cluster) = label else {
+            continue;
+        };
+        assert!(point.dim() >= 2, "cluster summaries need at least two coordinates, got {}", point.dim());
+        let summary = summaries.entry(*cluster).or_insert_with(|| ClusterSummary {
+            size: 0,
+            centroid: Point::zeros(point.dim()),
+            min_x: f64::INFINITY,
+            max_x: f64::NEG_INFINITY,
+            min_y: f64::INFINITY,
+            max_y: f64::NEG_INFINITY,
+        });
+        summary.size += 1;
+        summary.centroid = summary.centroid.add(point);
+        summary.min_x = summary.min_x.min(point.x());
+        summary.max_x = summary.max_x.max(point.x());
+        summary.min_y = summary.min_y.min(point.y());
+        summary.max_y = summary.max_y.max(point.y());
+    }
+
+    for summary in summaries.values_mut() {
+        summary.centroid = summary.centroid.scale(1.0 / summary.size as f64);
+    }
+
+    summaries
+}
+
+/// One line per cluster in ascending id order, then one line of noise
+/// points, each listing its points in input order. The output depends only
+/// on the arguments, so runs can be diffed.
+fn format_result(data: &[Point], labels: &[Option<usize>], types: &[PointType]) -> String {
+    let groups: BTreeMap<usize, Vec<usize>> = group_by_optional_label(data, labels)
+        .into_iter()
+        .filter_map(|(label, mut points)| {
+            points.sort_unstable();
+            label.map(|cluster| (cluster, points))
+        })
+        .collect();
+
+    let mut text = String::new();
+    for (cluster, points) in &groups {
+        let points: Vec<String> = points
+            .iter()
+            .map(|&i| {
+                let role = match types[i] {
+                    PointType::Core => "Core",
+                    PointType::Border => "Border",
+                    PointType::Noise | PointType::Unclassified => {
+                        unreachable!("clustered points are always Core or Border")
+                    }
+                };
+                format!("{:.1?}:{}", data[i].coords, role)
+            })
+            .collect();
+        text.push_str(&format!("Cluster {}: {:?}\n", cluster, points));
+    }
+
+    let noise: Vec<String> = (0..data.len())
+        .filter(|&i| labels[i].is_none())
+        .map(|i| format!("{:.1?}", data[i].coords))
+        .collect();
+    if !noise.is_empty() {
+        text.push_str(&format!("Noise points: {:?}\n", noise));
+    }
+
+    text
+}
+
+/// One row of a parameter sweep: the `(eps, min_points)` pair that was run
+/// and the labelled clustering it produced.
+#[derive(Debug, Clone)]
+struct DbscanSweepResult {
+    eps: f64,
+    min_points: usize,
+    result: DbscanResult,
+}
+
 impl DBSCAN {
     fn new(eps: f64, min_points: usize) -> Self {
-        DBSCAN { eps, min_points }
+        Self::with_convention(eps, min_points, MinPtsConvention::default())
+    }
+
+    fn with_convention(eps: f64, min_points: usize, convention: MinPtsConvention) -> Self {
+        DBSCAN {
+            eps,
+            min_points,
+            convention,
+            metric: Minkowski::new(2.0).into(),
+            treat_border_as_noise: false,
+        }
+    }
+
+    /// How many neighbors, not counting the point itself, a core point needs.
+    fn required_neighbors(&self) -> usize {
+        match self.convention {
+            MinPtsConvention::IncludingSelf => self.min_points.saturating_sub(1),
+            MinPtsConvention::ExcludingSelf => self.min_points,
+        }
     }
     
+    /// Whether `a` and `b` are within `eps` of each other. With the default
+    /// Euclidean metric the squared distance is compared against `eps²`,
+    /// skipping a square root per pair.
+    fn within_eps(&self, a: &Point, b: &Point) -> bool {
+        self.eps_measure(a, b) <= self.eps_bound(self.eps)
+    }
+
+    fn is_euclidean(&self) -> bool {
+        matches!(self.metric, Metric::Minkowski(Minkowski { p }) if p == 2.0)
+    }
+
+    /// What `within_eps` compares: the squared distance under the Euclidean
+    /// metric and the metric's distance otherwise. It grows with distance,
+    /// so it also orders neighbors.
+    fn eps_measure(&self, a: &Point, b: &Point) -> f64 {
+        if self.is_euclidean() {
+            a.distance_sq(b)
+        } else {
+            self.metric.distance(a, b)
+        }
+    }
+
+    /// The bound `eps_measure` is compared against for radius `eps`.
+    fn eps_bound(&self, eps: f64) -> f64 {
+        if self.is_euclidean() {
+            eps * eps
+        } else {
+            eps
+        }
+    }
+
     fn region_query(&self, data: &[Point], point_idx: usize) -> Vec<usize> {
-        let point = data[point_idx];
+        let point = &data[point_idx];
         data.iter()
             .enumerate()
-            .filter(|(i, p)| {
-                *i != point_idx && p.distance(&point) <= self.eps
-            })
+            .filter(|(i, p)| *i != point_idx && self.within_eps(p, point))
             .map(|(i, _)| i)
             .collect()
     }
     
-    fn expand_cluster(
-        &self, 
-        data: &[Point], 
+    /// Grows cluster `cluster_id` from the core point `point_idx`. With
+    /// `border_as_noise` a non-core point reached by the expansion stays noise
+    /// instead of becoming a border point.
+    fn expand_cluster<F>(
         point_idx: usize, 
         neighbors: Vec<usize>,
         cluster_id: usize,
-        clusters: &mut Vec<Option<usize>>,
-        point_types: &mut Vec<PointType>,
-    ) {
+        clusters: &mut [Option<usize>],
+        point_types: &mut [PointType],
+        border_as_noise: bool,
+        core_neighbors: &F,
+    ) where
+        F: Fn(usize) -> Option<Vec<usize>>,
+    {
         clusters[point_idx] = Some(cluster_id);
         point_types[point_idx] = PointType::Core;
         
         let mut seeds = VecDeque::from(neighbors);
         while let Some(current_idx) = seeds.pop_front() {
             if point_types[current_idx] == PointType::Noise {
-                clusters[current_idx] = Some(cluster_id);
-                point_types[current_idx] = PointType::Border;
+                if !border_as_noise {
+                    clusters[current_idx] = Some(cluster_id);
+                    point_types[current_idx] = PointType::Border;
+                }
                 continue;
             }
             
@@ -71,25 +280,31 @@ impl DBSCAN {
                 continue;
             }
             
-            clusters[current_idx] = Some(cluster_id);
-            
-            let new_neighbors = self.region_query(data, current_idx);
-            
-            if new_neighbors.len() >= self.min_points {
+            if let Some(new_neighbors) = core_neighbors(current_idx) {
+                clusters[current_idx] = Some(cluster_id);
                 point_types[current_idx] = PointType::Core;
                 for &neighbor_idx in &new_neighbors {
                     if clusters[neighbor_idx].is_none() || point_types[neighbor_idx] == PointType::Noise {
                         seeds.push_back(neighbor_idx);
                     }
                 }
+            } else if border_as_noise {
+                point_types[current_idx] = PointType::Noise;
             } else {
+                clusters[current_idx] = Some(cluster_id);
                 point_types[current_idx] = PointType::Border;
             }
         }
     }
     
-    fn fit(&self, data: &[Point]) -> (Vec<Option<usize>>, Vec<PointType>) {
-        let n = data.len();
+    /// Runs the DBSCAN labelling over `n` points. `core_neighbors` returns the
+    /// eps-neighborhood of a point if it is a core point and `None` otherwise,
+    /// which lets every variant share the same expansion logic. With
+    /// `border_as_noise` the labelling is DBSCAN*: no point is ever `Border`.
+    fn fit_with<F>(n: usize, border_as_noise: bool, core_neighbors: F) -> (Vec<Option<usize>>, Vec<PointType>)
+    where
+        F: Fn(usize) -> Option<Vec<usize>>,
+    {
         let mut clusters: Vec<Option<usize>> = vec![None; n];
         let mut point_types: Vec<PointType> = vec![PointType::Unclassified; n];
         
@@ -100,87 +315,866 @@ impl DBSCAN {
                 continue;
             }
             
-            let neighbors = self.region_query(data, i);
-            
-            if neighbors.len() < self.min_points {
+            let Some(neighbors) = core_neighbors(i) else {
                 point_types[i] = PointType::Noise;
                 continue;
-            }
+            };
             
             cluster_id += 1;
-            self.expand_cluster(data, i, neighbors, cluster_id, &mut clusters, &mut point_types);
+            Self::expand_cluster(
+                i,
+                neighbors,
+                cluster_id,
+                &mut clusters,
+                &mut point_types,
+                border_as_noise,
+                &core_neighbors,
+            );
         }
         
+        debug_assert!(
+            clusters.iter().zip(&point_types).all(|(cluster, point_type)| match point_type {
+                PointType::Core | PointType::Border => cluster.is_some(),
+                PointType::Noise => cluster.is_none(),
+                PointType::Unclassified => false,
+            }),
+            "every point must end up Core or Border with a cluster, or Noise without one"
+        );
+        
         (clusters, point_types)
     }
+    
+    /// Panics if a point has a NaN or infinite coordinate. With the
+    /// `parallel` feature the neighborhoods are computed by `fit_parallel`.
+    fn fit(&self, data: &[Point]) -> (Vec<Option<usize>>, Vec<PointType>) {
+        assert_finite(data);
+        #[cfg(feature = "parallel")]
+        {
+            self.fit_parallel(data)
+        }
+        #[cfg(not(feature = "parallel"))]
+        {
+            self.fit_serial(data)
+        }
+    }
+
+    /// `fit` as GDBSCAN with the eps/min_points predicates, querying each
+    /// neighborhood when the expansion reaches it. With the `parallel`
+    /// feature only the tests call it, as the reference for `fit_parallel`.
+    #[cfg_attr(feature = "parallel", allow(dead_code))]
+    fn fit_serial(&self, data: &[Point]) -> (Vec<Option<usize>>, Vec<PointType>) {
+        let required = self.required_neighbors();
+        let mut gdbscan = Gdbscan::new(
+            |a: &Point, b: &Point| self.within_eps(a, b),
+            |neighbors: &[usize]| neighbors.len() >= required,
+        );
+        gdbscan.treat_border_as_noise = self.treat_border_as_noise;
+        gdbscan.fit(data)
+    }
+
+    /// `fit` with every eps-neighborhood computed up front on the rayon thread
+    /// pool. The expansion then runs serially over those neighbor lists, so
+    /// the labels are exactly those of `fit_serial`.
+    #[cfg(feature = "parallel")]
+    fn fit_parallel(&self, data: &[Point]) -> (Vec<Option<usize>>, Vec<PointType>) {
+        use rayon::prelude::*;
+
+        let neighbors: Vec<Vec<usize>> =
+            (0..data.len()).into_par_iter().map(|i| self.region_query(data, i)).collect();
+        let required = self.required_neighbors();
+        Self::fit_with(data.len(), self.treat_border_as_noise, |i| {
+            (neighbors[i].len() >= required).then(|| neighbors[i].clone())
+        })
+    }
+
+    /// DBSCAN where each point stands for `weights[i]` merged observations.
+    /// A point is core when the mass of its neighborhood, excluding one
+    /// observation for the point itself, reaches the neighbor count required
+    /// by the `min_points` convention; the point's own extra weight counts
+    /// because its merged observations are at distance zero. Unit weights
+    /// reproduce `fit` exactly.
+    fn fit_weighted(&self, data: &[Point], weights: &[f64]) -> (Vec<Option<usize>>, Vec<PointType>) {
+        assert_eq!(
+            data.len(),
+            weights.len(),
+            "expected one weight per point, got {} weights for {} points",
+            weights.len(),
+            data.len()
+        );
+        assert_finite(data);
+
+        Self::fit_with(data.len(), self.treat_border_as_noise, |i| {
+            let neighbors = self.region_query(data, i);
+            let mass = weights[i] - 1.0 + neighbors.iter().map(|&j| weights[j]).sum::<f64>();
+            (mass >= self.required_neighbors() as f64).then_some(neighbors)
+        })
+    }
+
+    /// Assigns `new_point` to the cluster of the nearest core point within
+    /// `eps`, or `None` (noise) if no core point is that close. The fitted
+    /// model is not modified. Panics if `new_point` or a point of `data` has a
+    /// NaN or infinite coordinate.
+    fn predict(
+        &self,
+        data: &[Point],
+        labels: &[Option<usize>],
+        point_types: &[PointType],
+        new_point: &Point,
+    ) -> Option<usize> {
+        assert_finite(data);
+        assert_finite(std::slice::from_ref(new_point));
+        let mut nearest: Option<(f64, usize)> = None;
+
+        for (i, point) in data.iter().enumerate() {
+            if point_types[i] != PointType::Core {
+                continue;
+            }
+            let Some(cluster) = labels[i] else {
+                continue;
+            };
+
+            let measure = self.eps_measure(point, new_point);
+            if measure <= self.eps_bound(self.eps)
+                && nearest.is_none_or(|(best, _)| measure.total_cmp(&best).is_lt())
+            {
+                nearest = Some((measure, cluster));
+            }
+        }
+
+        nearest.map(|(_, cluster)| cluster)
+    }
+
+    /// The density-reachability graph as an adjacency list indexed by point:
+    /// a core point lists its eps-neighborhood, the points directly
+    /// density-reachable from it, and every other point has no outgoing
+    /// edges. The clusters of `fit` are the connected components of the core
+    /// points, with each border point attached to a core point listing it.
+    /// Panics if a point has a NaN or infinite coordinate.
+    fn reachability_graph(&self, data: &[Point]) -> Vec<Vec<usize>> {
+        assert_finite(data);
+        let required = self.required_neighbors();
+        (0..data.len())
+            .map(|i| {
+                let neighbors = self.region_query(data, i);
+                if neighbors.len() >= required {
+                    neighbors
+                } else {
+                    Vec::new()
+                }
+            })
+            .collect()
+    }
+
+    fn fit_labeled(&self, data: &[Point]) -> DbscanResult {
+        let (clusters, point_types) = self.fit(data);
+        DbscanResult::from_fit(clusters, point_types)
+    }
+
+    /// Every point's neighbors within `max_eps` under this model's metric,
+    /// with their `eps_measure`, in index order. Computed once, it serves any
+    /// `fit_precomputed` with `eps <= max_eps` and the same metric. Panics if
+    /// a point has a NaN or infinite coordinate, which covers
+    /// `fit_precomputed` and `sweep` since they never see the points.
+    fn precompute_neighbors(&self, data: &[Point], max_eps: f64) -> Vec<Vec<(usize, f64)>> {
+        assert_finite(data);
+        let bound = self.eps_bound(max_eps);
+        (0..data.len())
+            .map(|i| {
+                data.iter()
+                    .enumerate()
+                    .filter(|&(j, _)| j != i)
+                    .map(|(j, point)| (j, self.eps_measure(point, &data[i])))
+                    .filter(|&(_, measure)| measure <= bound)
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// `fit` on neighbor lists from `precompute_neighbors`, keeping only
+    /// neighbors within `self.eps`. The stored measures are compared exactly
+    /// as `within_eps` compares them, so the labels match `fit`.
+    fn fit_precomputed(
+        &self,
+        neighbors: &[Vec<(usize, f64)>],
+        data_len: usize,
+    ) -> (Vec<Option<usize>>, Vec<PointType>) {
+        let required = self.required_neighbors();
+        let bound = self.eps_bound(self.eps);
+        Self::fit_with(data_len, self.treat_border_as_noise, |i| {
+            let within_eps: Vec<usize> = neighbors[i]
+                .iter()
+                .filter(|&&(_, measure)| measure <= bound)
+                .map(|&(j, _)| j)
+                .collect();
+            (within_eps.len() >= required).then_some(within_eps)
+        })
+    }
 }
 
-fn main() {
-    let data = vec![
-        Point::new(1.0, 1.0),
-        Point::new(1.0, 8.0),
-        Point::new(2.0, 2.0),
-        Point::new(2.0, 5.0),
-        Point::new(3.0, 1.0),
-        Point::new(4.0, 3.0),
-        Point::new(5.0, 2.0),
-        Point::new(6.0, 1.0),
-        Point::new(6.0, 8.0),
-        Point::new(8.0, 6.0),
-    ];
+impl Clusterer for DBSCAN {
+    fn fit(&mut self, data: &[Point]) -> Vec<Option<usize>> {
+        DBSCAN::fit(self, data).0
+    }
+}
+
+/// Runs DBSCAN once per `(eps, min_points)` pair so parameters can be compared
+/// programmatically instead of by reading console output. Neighborhoods are
+/// computed once for the largest `eps` and reused by every run.
+fn sweep(data: &[Point], params: &[(f64, usize)]) -> Vec<DbscanSweepResult> {
+    let max_eps = params.iter().map(|&(eps, _)| eps).fold(0.0, f64::max);
+    let neighbors = DBSCAN::new(max_eps, 0).precompute_neighbors(data, max_eps);
+
+    params
+        .iter()
+        .map(|&(eps, min_points)| {
+            let (clusters, point_types) = DBSCAN::new(eps, min_points).fit_precomputed(&neighbors, data.len());
+            DbscanSweepResult {
+                eps,
+                min_points,
+                result: DbscanResult::from_fit(clusters, point_types),
+            }
+        })
+        .collect()
+}
+
+/// Every point's distance to its `k`-th nearest other point, sorted
+/// ascending: the k-distance graph used to choose `eps` for
+/// `min_points = k`. Distances are infinite for `k >= data.len()`.
+fn k_distances(data: &[Point], k: usize) -> Vec<f64> {
+    let mut distances = core_distances(data, k);
+    sort_distances(&mut distances);
+    distances
+}
+
+/// Suggests `eps` at the knee of the k-distance graph (kneedle): after
+/// scaling both axes to `[0, 1]`, the distance whose point lies farthest
+/// below the line joining the two endpoints.
+fn suggest_eps(data: &[Point], k: usize) -> f64 {
+    assert!(
+        k >= 1 && k < data.len(),
+        "k must be between 1 and {} for {} points, got {}",
+        data.len().saturating_sub(1),
+        data.len(),
+        k
+    );
+
+    let distances = k_distances(data, k);
+    let first = distances[0];
+    let last = distances[distances.len() - 1];
+    let x_span = (distances.len() - 1).max(1) as f64;
+    let y_span = (last - first).max(f64::EPSILON);
+
+    distances
+        .iter()
+        .enumerate()
+        .map(|(i, &distance)| (distance, i as f64 / x_span - (distance - first) / y_span))
+        .max_by(|a, b| a.1.total_cmp(&b.1))
+        .map(|(distance, _)| distance)
+        .unwrap_or(first)
+}
+
+fn main() -> io::Result<()> {
+    let args = Args::from_env_or_exit(&[cli::INPUT, cli::EPS, cli::MIN_POINTS]);
+    let data = match args.value_or_exit::<String>(cli::INPUT) {
+        Some(path) => read_points(BufReader::new(File::open(path)?))?,
+        None => vec![
+            Point::new(1.0, 1.0),
+            Point::new(1.0, 8.0),
+            Point::new(2.0, 2.0),
+            Point::new(2.0, 5.0),
+            Point::new(3.0, 1.0),
+            Point::new(4.0, 3.0),
+            Point::new(5.0, 2.0),
+            Point::new(6.0, 1.0),
+            Point::new(6.0, 8.0),
+            Point::new(8.0, 6.0),
+        ],
+    };
+    
+    if data.len() > 2 {
+        println!("k-distance graph (k = 2): {:.3?}", k_distances(&data, 2));
+        println!("Suggested eps for min_points = 2: {:.3}", suggest_eps(&data, 2));
+    }
     
-    let test_params = vec![
-        (1.5, 2),
-        (2.0, 2),
-        (2.5, 2),
-        (3.0, 2),
+    let blobs: Vec<Point> = vec![
+        (0.0, 0.0).into(),
+        (0.3, 0.1).into(),
+        (0.1, 0.4).into(),
+        (0.4, 0.3).into(),
+        (20.0, 20.0).into(),
+        (20.2, 19.9).into(),
+        (19.8, 20.3).into(),
+        (20.1, 20.4).into(),
     ];
+    println!(
+        "Suggested eps for two blobs about 28 apart (min_points = 2): {:.3}",
+        suggest_eps(&blobs, 2)
+    );
     
-    for (eps, min_points) in test_params {
+    let eps_values = match args.value_or_exit::<f64>(cli::EPS) {
+        Some(eps) => vec![eps],
+        None => vec![1.5, 2.0, 2.5, 3.0],
+    };
+    let min_points = args.value_or_exit::<usize>(cli::MIN_POINTS).unwrap_or(2);
+    let test_params: Vec<(f64, usize)> = eps_values.into_iter().map(|eps| (eps, min_points)).collect();
+    
+    for DbscanSweepResult { eps, min_points, result } in sweep(&data, &test_params) {
         println!("\nRunning DBSCAN with eps = {}, min_points = {}", eps, min_points);
         
-        let dbscan = DBSCAN::new(eps, min_points);
-        let (clusters, point_types) = dbscan.fit(&data);
+        println!("Found {} clusters and {} noise points", result.cluster_count(), result.noise_count());
         
-        let mut unique_clusters = HashSet::new();
-        let mut noise_count = 0;
+        let (clustered, labels): (Vec<Point>, Vec<usize>) = data
+            .iter()
+            .zip(&result.labels)
+            .filter(|(_, &label)| label >= 0)
+            .map(|(point, &label)| (point.clone(), label as usize))
+            .unzip();
+        println!("Davies-Bouldin index (noise excluded): {:.4}", davies_bouldin(&clustered, &labels));
+        println!(
+            "Within-cluster SSE (noise excluded): {:.4}",
+            cluster_sse(&clustered, &labels.iter().copied().map(Some).collect::<Vec<_>>())
+        );
         
-        for cluster in &clusters {
-            match cluster {
-                Some(id) => { unique_clusters.insert(id); },
-                None => noise_count += 1,
+        let optional_labels: Vec<Option<usize>> =
+            result.labels.iter().map(|&label| usize::try_from(label).ok()).collect();
+        print!("{}", format_result(&data, &optional_labels, &result.point_types));
+    }    
+    println!("\nDensity-reachability graph with eps = 2.0, min_points = 2");
+    let dbscan = DBSCAN::new(2.0, 2);
+    let graph = dbscan.reachability_graph(&data);
+    for (i, edges) in graph.iter().enumerate().filter(|(_, edges)| !edges.is_empty()) {
+        println!("{} -> {:?}", i, edges);
+    }
+    
+    println!("\nPredicting new points with eps = 2.0, min_points = 2");
+    let dbscan = DBSCAN::new(2.0, 2);
+    let (clusters, point_types) = dbscan.fit(&data);
+    let summaries = if data.first().is_some_and(|point| point.dim() >= 2) {
+        cluster_summaries(&data, &clusters)
+    } else {
+        HashMap::new()
+    };
+    let mut cluster_ids: Vec<&usize> = summaries.keys().collect();
+    cluster_ids.sort();
+    for cluster in cluster_ids {
+        let summary = &summaries[cluster];
+        println!(
+            "Cluster {}: {} points, centroid {:.2?}, x in [{:.1}, {:.1}], y in [{:.1}, {:.1}]",
+            cluster,
+            summary.size,
+            summary.centroid.coords,
+            summary.min_x,
+            summary.max_x,
+            summary.min_y,
+            summary.max_y
+        );
+    }
+    // The query points are planar, so only predict against planar input.
+    if data.first().is_some_and(|point| point.dim() == 2) {
+        for new_point in [Point::new(2.0, 1.0), Point::new(5.5, 2.5), Point::new(4.0, 6.0)] {
+            match dbscan.predict(&data, &clusters, &point_types, &new_point) {
+                Some(cluster) => println!("{:.1?} -> Cluster {}", new_point.coords, cluster),
+                None => println!("{:.1?} -> Noise", new_point.coords),
             }
         }
-        
-        println!("Found {} clusters and {} noise points", unique_clusters.len(), noise_count);
-        
-        let mut cluster_map: HashMap<Option<usize>, Vec<(f64, f64, PointType)>> = HashMap::new();
-        
-        for (i, cluster) in clusters.iter().enumerate() {
-            let point = data[i];
-            let point_type = point_types[i];
-            cluster_map.entry(*cluster).or_insert_with(Vec::new)
-                .push((point.x, point.y, point_type));
+    }
+    
+    println!("\nPoint (2.0,2.0) has two neighbors at eps = 1.5; with min_points = 3 it is:");
+    for convention in [MinPtsConvention::IncludingSelf, MinPtsConvention::ExcludingSelf] {
+        let (_, point_types) = DBSCAN::with_convention(1.5, 3, convention).fit(&data);
+        if let Some(point_type) = point_types.get(2) {
+            println!("{:?}: {:?}", convention, point_type);
         }
-        
-        if let Some(points) = cluster_map.get(&None) {
-            println!("Noise points: {:?}", points.iter()
-                .map(|(x, y, _)| format!("({:.1},{:.1})", x, y))
-                .collect::<Vec<_>>());
+    }
+    
+    println!("\nDBSCAN vs DBSCAN* (border points as noise) with eps = 2.0, min_points = 2:");
+    let mut dbscan_star = DBSCAN::new(2.0, 2);
+    dbscan_star.treat_border_as_noise = true;
+    for (name, dbscan) in [("DBSCAN", DBSCAN::new(2.0, 2)), ("DBSCAN*", dbscan_star)] {
+        let (clusters, point_types) = dbscan.fit(&data);
+        let result = DbscanResult::from_fit(clusters, point_types);
+        let borders = result.point_types.iter().filter(|&&t| t == PointType::Border).count();
+        println!(
+            "{}: {} clusters, {} noise points, {} border points",
+            name,
+            result.cluster_count(),
+            result.noise_count(),
+            borders
+        );
+    }
+    
+    println!("\nRunning GDBSCAN with eps = 2.0 in space, min_points = 2 and a category per point");
+    let categorized: Vec<Point> = data
+        .iter()
+        .enumerate()
+        .map(|(i, point)| Point::from_coords(point.coords.iter().copied().chain([(i % 2) as f64]).collect()))
+        .collect();
+    // The category is the last coordinate; eps applies to the ones before it.
+    let spatial = |point: &Point| Point::from_slice(&point.coords[..point.dim() - 1]);
+    let same_category_within_eps = |a: &Point, b: &Point| {
+        a.coords.last() == b.coords.last() && spatial(a).distance(&spatial(b)) <= 2.0
+    };
+    let (clusters, _) = Gdbscan::new(same_category_within_eps, |neighbors: &[usize]| neighbors.len() >= 2)
+        .fit(&categorized);
+    for (i, (point, cluster)) in data.iter().zip(&clusters).enumerate() {
+        println!("{:.1?} category {}: {:?}", point.coords, i % 2, cluster);
+    }
+    
+    println!("\nRunning DBSCAN with eps = 2.0, min_points = 2 treating the category as categorical");
+    let mut categorical = vec![false; data.first().map_or(0, Point::dim)];
+    categorical.push(true);
+    let mixed = MixedDistance::new(categorical);
+    let (clusters, _) = Gdbscan::new(
+        |a: &Point, b: &Point| mixed.distance(a, b) <= 2.0,
+        |neighbors: &[usize]| neighbors.len() >= 2,
+    )
+    .fit(&categorized);
+    println!("Cluster assignments: {:?}", clusters);
+    
+    println!("\nRunning DBSCAN with eps = 2.5, min_points = 2 under different Minkowski exponents");
+    for p in [1.0, 2.0, 4.0] {
+        let mut dbscan = DBSCAN::new(2.5, 2);
+        dbscan.metric = Minkowski::new(p).into();
+        let result = dbscan.fit_labeled(&data);
+        println!(
+            "p = {}: {} clusters and {} noise points",
+            p,
+            result.cluster_count(),
+            result.noise_count()
+        );
+    }
+    
+    println!("\nRunning DBSCAN with eps = 0.3, min_points = 1 on proportions");
+    let proportions = vec![
+        Point::new(0.7, 0.3),
+        Point::new(0.65, 0.35),
+        Point::new(0.2, 0.8),
+        Point::new(0.25, 0.75),
+        Point::new(0.0, 1.0),
+    ];
+    for (name, metric) in [("Canberra", Metric::Canberra), ("Bray-Curtis", Metric::BrayCurtis)] {
+        let mut dbscan = DBSCAN::new(0.3, 1);
+        dbscan.metric = metric;
+        let (clusters, _) = dbscan.fit(&proportions);
+        println!("{}: {:?}", name, clusters);
+    }
+    
+    println!("\nRunning weighted DBSCAN with eps = 1.5, min_points = 2 where point 3 has weight 3");
+    let mut weights = vec![1.0; data.len()];
+    if let Some(weight) = weights.get_mut(3) {
+        *weight = 3.0;
+    }
+    let (clusters, point_types) = DBSCAN::new(1.5, 2).fit_weighted(&data, &weights);
+    for (i, point) in data.iter().enumerate() {
+        println!("{:.1?} weight {}: {:?} {:?}", point.coords, weights[i], clusters[i], point_types[i]);
+    }
+    
+    println!("\nMutual reachability with min_points = 2");
+    let core = core_distances(&data, 2);
+    for (i, j) in [(0, 2), (0, 4), (1, 3), (8, 9)].into_iter().filter(|&(_, j)| j < data.len()) {
+        println!(
+            "{:.1?} - {:.1?}: distance {:.3}, core {:.3} / {:.3}, mutual reachability {:.3}",
+            data[i].coords,
+            data[j].coords,
+            data[i].distance(&data[j]),
+            core[i],
+            core[j],
+            mutual_reachability(&data, &core, i, j)
+        );
+    }
+    
+    println!("\nRunning OPTICS with eps = 3.0, min_points = 2");
+    let ordering = Optics::new(3.0, 2).fit(&data);
+    for entry in &ordering {
+        let point = &data[entry.index];
+        println!(
+            "{:.1?} core: {}, reachability: {}",
+            point.coords,
+            entry.core_distance.map_or("undefined".to_string(), |d| format!("{:.3}", d)),
+            entry.reachability.map_or("undefined".to_string(), |d| format!("{:.3}", d)),
+        );
+    }
+    
+    for eps_prime in [1.5, 2.0, 2.5, 3.0] {
+        let clusters = extract_dbscan(&ordering, eps_prime);
+        let cluster_count = clusters.iter().flatten().max().copied().unwrap_or(0);
+        let noise_count = clusters.iter().filter(|c| c.is_none()).count();
+        let (direct, _) = DBSCAN::new(eps_prime, 2).fit(&data);
+        println!(
+            "OPTICS extraction at eps' = {}: {} clusters and {} noise points (ARI vs DBSCAN: {:.3})",
+            eps_prime,
+            cluster_count,
+            noise_count,
+            adjusted_rand_index(&clusters, &direct)
+        );
+    }
+    
+    println!("\nScoring DBSCAN through the Clusterer trait (noise excluded)");
+    let mut clusterers: Vec<(&str, Box<dyn Clusterer>)> = vec![
+        ("eps = 2.0, min_points = 2", Box::new(DBSCAN::new(2.0, 2))),
+        ("eps = 3.0, min_points = 2", Box::new(DBSCAN::new(3.0, 2))),
+    ];
+    for (name, clusterer) in clusterers.iter_mut() {
+        let (silhouette, db_index) = evaluate(clusterer.as_mut(), &data);
+        println!("{}: silhouette {:.4}, Davies-Bouldin {:.4}", name, silhouette, db_index);
+    }
+    
+    let mut dbscan_star = DBSCAN::new(2.0, 2);
+    dbscan_star.treat_border_as_noise = true;
+    let reports = [
+        ("DBSCAN eps = 2.0", DBSCAN::new(2.0, 2).fit(&data).0),
+        ("DBSCAN eps = 3.0", DBSCAN::new(3.0, 2).fit(&data).0),
+        ("DBSCAN* eps = 2.0", dbscan_star.fit(&data).0),
+        ("OPTICS eps' = 2.0", extract_dbscan(&Optics::new(3.0, 2).fit(&data), 2.0)),
+    ];
+    print!("\nComparison (min_points = 2):\n{}", compare_clusterings(&data, &reports));
+    
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> Vec<Point> {
+        vec![
+            Point::new(1.0, 1.0),
+            Point::new(1.0, 8.0),
+            Point::new(2.0, 2.0),
+            Point::new(2.0, 5.0),
+            Point::new(3.0, 1.0),
+            Point::new(4.0, 3.0),
+            Point::new(5.0, 2.0),
+            Point::new(6.0, 1.0),
+            Point::new(6.0, 8.0),
+            Point::new(8.0, 6.0),
+        ]
+    }
+
+    const SWEEP: [(f64, usize); 4] = [(1.5, 2), (2.0, 2), (2.5, 2), (3.0, 2)];
+
+    #[test]
+    fn squared_eps_matches_sqrt_labels_across_the_sweep() {
+        let data = sample();
+        for (eps, min_points) in SWEEP {
+            let required = DBSCAN::new(eps, min_points).required_neighbors();
+            let (sqrt_clusters, _) = Gdbscan::new(
+                |a: &Point, b: &Point| a.distance(b) <= eps,
+                |neighbors: &[usize]| neighbors.len() >= required,
+            )
+            .fit(&data);
+            assert_eq!(DBSCAN::new(eps, min_points).fit(&data).0, sqrt_clusters, "eps = {}", eps);
         }
-        
-        for cluster_id in unique_clusters {
-            if let Some(points) = cluster_map.get(&Some(*cluster_id)) {
-                println!("Cluster {}: {:?}", cluster_id, points.iter()
-                    .map(|(x, y, pt)| format!("({:.1},{:.1}):{}", x, y, 
-                        match pt {
-                            PointType::Core => "Core",
-                            PointType::Border => "Border",
-                            _ => "Unknown",
+    }
+
+    #[test]
+    fn sweep_matches_fit_for_every_eps() {
+        let data = sample();
+        for (row, (eps, min_points)) in sweep(&data, &SWEEP).iter().zip(SWEEP) {
+            let (clusters, point_types) = DBSCAN::new(eps, min_points).fit(&data);
+            let expected = DbscanResult::from_fit(clusters, point_types);
+            assert_eq!(row.result.labels, expected.labels, "eps = {}", eps);
+            assert_eq!(row.result.point_types, expected.point_types, "eps = {}", eps);
+        }
+    }
+
+    #[test]
+    fn sweep_matches_fit_for_a_point_at_exactly_eps() {
+        // sqrt(3)² rounds below 3, so the pair is outside eps = sqrt(3) when
+        // compared squared although its rounded distance equals eps.
+        let data = vec![Point::from_slice(&[0.0, 0.0, 0.0]), Point::from_slice(&[1.0, 1.0, 1.0])];
+        let eps = 3f64.sqrt();
+        let dbscan = DBSCAN::new(eps, 1);
+        let neighbors = dbscan.precompute_neighbors(&data, eps);
+
+        assert_eq!(dbscan.fit_precomputed(&neighbors, data.len()), dbscan.fit(&data));
+        assert_eq!(sweep(&data, &[(eps, 1)])[0].result.labels, vec![-1, -1]);
+    }
+
+    fn with_nan() -> Vec<Point> {
+        let mut data = sample();
+        data[3] = Point::new(f64::NAN, 5.0);
+        data
+    }
+
+    #[test]
+    #[should_panic(expected = "point 3")]
+    fn fit_rejects_a_nan_point() {
+        DBSCAN::new(2.0, 2).fit(&with_nan());
+    }
+
+    #[test]
+    #[should_panic(expected = "point 3")]
+    fn precompute_neighbors_rejects_a_nan_point() {
+        DBSCAN::new(2.0, 2).precompute_neighbors(&with_nan(), 3.0);
+    }
+
+    #[test]
+    #[should_panic(expected = "point 3")]
+    fn reachability_graph_rejects_a_nan_point() {
+        DBSCAN::new(2.0, 2).reachability_graph(&with_nan());
+    }
+
+    #[test]
+    #[should_panic(expected = "point 0")]
+    fn predict_rejects_a_nan_query() {
+        let data = sample();
+        let dbscan = DBSCAN::new(2.0, 2);
+        let (clusters, point_types) = dbscan.fit(&data);
+        dbscan.predict(&data, &clusters, &point_types, &Point::new(1.0, f64::INFINITY));
+    }
+
+    #[test]
+    #[should_panic(expected = "point 3")]
+    fn optics_rejects_a_nan_point() {
+        Optics::new(2.0, 2).fit(&with_nan());
+    }
+
+    #[test]
+    fn noise_is_labelled_minus_one_and_counted() {
+        let result = DBSCAN::new(1.5, 2).fit_labeled(&sample());
+
+        let minus_ones = result.labels.iter().filter(|&&label| label == -1).count();
+        assert_eq!(minus_ones, result.noise_count());
+        assert_eq!(result.noise_count(), 4);
+        assert_eq!(result.cluster_count(), 2);
+        assert_eq!(result.cluster_sizes[&0], 3);
+        assert_eq!(result.cluster_sizes[&1], 3);
+    }
+
+    #[test]
+    fn predict_uses_eps_around_core_points() {
+        let data = sample();
+        let dbscan = DBSCAN::new(2.0, 2);
+        let (clusters, point_types) = dbscan.fit(&data);
+        assert_eq!(point_types[2], PointType::Core);
+
+        // (2, 2) is the only core point near either query.
+        let inside = Point::new(2.0, 3.9);
+        let outside = Point::new(2.0, 4.1);
+        assert_eq!(dbscan.predict(&data, &clusters, &point_types, &inside), clusters[2]);
+        assert_eq!(dbscan.predict(&data, &clusters, &point_types, &outside), None);
+    }
+
+    #[test]
+    fn a_heavy_point_is_core_on_its_own() {
+        let data = vec![Point::new(0.0, 0.0), Point::new(10.0, 10.0), Point::new(20.0, 20.0)];
+        let dbscan = DBSCAN::new(1.0, 2);
+
+        let (clusters, point_types) = dbscan.fit_weighted(&data, &[3.0, 1.0, 1.0]);
+        assert_eq!(point_types, vec![PointType::Core, PointType::Noise, PointType::Noise]);
+        assert_eq!(clusters, vec![Some(1), None, None]);
+
+        assert_eq!(dbscan.fit_weighted(&data, &[1.0; 3]), dbscan.fit(&data));
+    }
+
+    #[test]
+    fn growing_eps_never_adds_noise() {
+        let noise: Vec<usize> = sweep(&sample(), &SWEEP).iter().map(|row| row.result.noise_count()).collect();
+        assert!(noise.windows(2).all(|pair| pair[1] <= pair[0]), "{:?}", noise);
+        assert_eq!(noise, vec![4, 4, 4, 3]);
+    }
+
+    #[test]
+    fn min_points_convention_decides_the_boundary_case() {
+        // (2, 2) has exactly two neighbors within 1.5: min_points - 1.
+        let data = sample();
+        assert_eq!(DBSCAN::new(1.5, 3).region_query(&data, 2).len(), 2);
+
+        let including = DBSCAN::with_convention(1.5, 3, MinPtsConvention::IncludingSelf).fit(&data).1;
+        let excluding = DBSCAN::with_convention(1.5, 3, MinPtsConvention::ExcludingSelf).fit(&data).1;
+        assert_eq!(including[2], PointType::Core);
+        assert_eq!(excluding[2], PointType::Noise);
+    }
+
+    #[test]
+    fn fit_precomputed_at_max_eps_matches_fit() {
+        let data = sample();
+        for (eps, min_points) in SWEEP {
+            let dbscan = DBSCAN::new(eps, min_points);
+            let neighbors = dbscan.precompute_neighbors(&data, eps);
+            assert_eq!(dbscan.fit_precomputed(&neighbors, data.len()), dbscan.fit(&data), "eps = {}", eps);
+        }
+    }
+
+    #[test]
+    fn summaries_stay_in_their_boxes_and_cover_the_clustered_points() {
+        let data = sample();
+        for (eps, min_points) in SWEEP {
+            let (clusters, _) = DBSCAN::new(eps, min_points).fit(&data);
+            let summaries = cluster_summaries(&data, &clusters);
+
+            for summary in summaries.values() {
+                assert!((summary.min_x..=summary.max_x).contains(&summary.centroid.x()));
+                assert!((summary.min_y..=summary.max_y).contains(&summary.centroid.y()));
+            }
+            let sizes: usize = summaries.values().map(|summary| summary.size).sum();
+            assert_eq!(sizes, clusters.iter().flatten().count(), "eps = {}", eps);
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "at least two coordinates")]
+    fn summaries_reject_one_dimensional_points() {
+        let data = vec![Point::from_coords(vec![1.0]), Point::from_coords(vec![1.5])];
+        cluster_summaries(&data, &[Some(0), Some(0)]);
+    }
+
+    #[test]
+    fn suggested_eps_for_two_far_blobs_is_small() {
+        let blobs: Vec<Point> = vec![
+            (0.0, 0.0).into(),
+            (0.3, 0.1).into(),
+            (0.1, 0.4).into(),
+            (0.4, 0.3).into(),
+            (20.0, 20.0).into(),
+            (20.2, 19.9).into(),
+            (19.8, 20.3).into(),
+            (20.1, 20.4).into(),
+        ];
+        let gap = blobs[0].distance(&blobs[4]);
+
+        let eps = suggest_eps(&blobs, 2);
+        assert!(eps > 0.0 && eps < gap / 10.0, "eps {} for blobs {} apart", eps, gap);
+        assert_eq!(DBSCAN::new(eps, 2).fit_labeled(&blobs).cluster_count(), 2);
+    }
+
+    #[test]
+    fn no_point_is_left_unclassified() {
+        let data = sample();
+        for (eps, min_points) in SWEEP {
+            let (clusters, point_types) = DBSCAN::new(eps, min_points).fit(&data);
+            for (cluster, point_type) in clusters.iter().zip(&point_types) {
+                assert_ne!(*point_type, PointType::Unclassified, "eps = {}", eps);
+                assert_eq!(
+                    cluster.is_some(),
+                    matches!(point_type, PointType::Core | PointType::Border),
+                    "eps = {}",
+                    eps
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn graph_components_are_the_clusters() {
+        let data = sample();
+        for (eps, min_points) in SWEEP {
+            let dbscan = DBSCAN::new(eps, min_points);
+            let graph = dbscan.reachability_graph(&data);
+            let (clusters, point_types) = dbscan.fit(&data);
+            let is_core = |i: usize| point_types[i] == PointType::Core;
+
+            // Label every core point with the smallest core index of its
+            // component.
+            let mut component: Vec<Option<usize>> = vec![None; data.len()];
+            for start in (0..data.len()).filter(|&i| is_core(i)) {
+                if component[start].is_some() {
+                    continue;
+                }
+                component[start] = Some(start);
+                let mut stack = vec![start];
+                while let Some(current) = stack.pop() {
+                    for &next in graph[current].iter().filter(|&&next| is_core(next)) {
+                        if component[next].is_none() {
+                            component[next] = Some(start);
+                            stack.push(next);
                         }
-                    ))
-                    .collect::<Vec<_>>());
+                    }
+                }
+            }
+
+            let cores: Vec<usize> = (0..data.len()).filter(|&i| is_core(i)).collect();
+            for &i in &cores {
+                for &j in &cores {
+                    assert_eq!(component[i] == component[j], clusters[i] == clusters[j], "eps = {}", eps);
+                }
             }
+            for border in (0..data.len()).filter(|&i| point_types[i] == PointType::Border) {
+                assert!(
+                    cores.iter().any(|&core| graph[core].contains(&border) && clusters[core] == clusters[border]),
+                    "border point {} at eps = {}",
+                    border,
+                    eps
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn dbscan_star_has_no_border_points_and_at_least_as_much_noise() {
+        let data = sample();
+        for (eps, min_points) in SWEEP {
+            let mut dbscan_star = DBSCAN::new(eps, min_points);
+            dbscan_star.treat_border_as_noise = true;
+            let star = dbscan_star.fit_labeled(&data);
+            let standard = DBSCAN::new(eps, min_points).fit_labeled(&data);
+
+            assert!(star.noise_count() >= standard.noise_count(), "eps = {}", eps);
+            assert!(!star.point_types.contains(&PointType::Border), "eps = {}", eps);
+        }
+    }
+
+    #[test]
+    fn printed_result_is_identical_across_fits() {
+        let data = sample();
+        let dbscan = DBSCAN::new(2.0, 2);
+        let (clusters, point_types) = dbscan.fit(&data);
+        let (again, again_types) = dbscan.fit(&data);
+
+        let first = format_result(&data, &clusters, &point_types);
+        assert_eq!(first, format_result(&data, &again, &again_types));
+        assert_eq!(
+            first,
+            "Cluster 1: [\"[1.0, 1.0]:Core\", \"[2.0, 2.0]:Core\", \"[3.0, 1.0]:Core\"]\n\
+             Cluster 2: [\"[4.0, 3.0]:Border\", \"[5.0, 2.0]:Core\", \"[6.0, 1.0]:Border\"]\n\
+             Noise points: [\"[1.0, 8.0]\", \"[2.0, 5.0]\", \"[6.0, 8.0]\", \"[8.0, 6.0]\"]\n"
+        );
+    }
+
+    #[test]
+    fn printed_result_keeps_every_coordinate() {
+        let line: Vec<Point> = [0.0, 0.5, 1.0, 9.0].iter().map(|&x| Point::from_coords(vec![x])).collect();
+        let (clusters, point_types) = DBSCAN::new(1.0, 2).fit(&line);
+        assert_eq!(
+            format_result(&line, &clusters, &point_types),
+            "Cluster 1: [\"[0.0]:Core\", \"[0.5]:Core\", \"[1.0]:Core\"]\nNoise points: [\"[9.0]\"]\n"
+        );
+
+        let cube = vec![
+            Point::from_slice(&[0.0, 0.0, 0.0]),
+            Point::from_slice(&[0.5, 0.5, 0.5]),
+            Point::from_slice(&[0.0, 0.5, 0.0]),
+        ];
+        let (clusters, point_types) = DBSCAN::new(1.0, 2).fit(&cube);
+        assert_eq!(
+            format_result(&cube, &clusters, &point_types),
+            "Cluster 1: [\"[0.0, 0.0, 0.0]:Core\", \"[0.5, 0.5, 0.5]:Core\", \
+             \"[0.0, 0.5, 0.0]:Core\"]\n"
+        );
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn parallel_and_serial_fits_agree_across_the_sweep() {
+        let data = sample();
+        for (eps, min_points) in SWEEP {
+            let dbscan = DBSCAN::new(eps, min_points);
+            assert_eq!(dbscan.fit_parallel(&data), dbscan.fit_serial(&data), "eps = {}", eps);
+        }
+    }
+
+    #[test]
+    fn boxed_clusterers_match_a_direct_fit() {
+        let data = sample();
+        let mut clusterers: Vec<Box<dyn Clusterer>> = SWEEP
+            .iter()
+            .map(|&(eps, min_points)| Box::new(DBSCAN::new(eps, min_points)) as Box<dyn Clusterer>)
+            .collect();
+
+        for (clusterer, (eps, min_points)) in clusterers.iter_mut().zip(SWEEP) {
+            let labels = clusterer.fit(&data);
+            assert_eq!(labels, DBSCAN::new(eps, min_points).fit(&data).0, "eps = {}", eps);
         }
     }
 }
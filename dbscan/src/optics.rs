@@ -0,0 +1,186 @@
+use common::{assert_finite, sort_distances, Distance};
+
+use crate::{Point, DBSCAN};
+
+/// One step of the OPTICS cluster ordering. `core_distance` is `None` for
+/// points that are not core at `eps`, and `reachability` is `None` for the
+/// first point of every density-connected component.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct OpticsEntry {
+    pub index: usize,
+    pub core_distance: Option<f64>,
+    pub reachability: Option<f64>,
+}
+
+pub struct Optics {
+    dbscan: DBSCAN,
+}
+
+impl Optics {
+    pub fn new(eps: f64, min_points: usize) -> Self {
+        Optics {
+            dbscan: DBSCAN::new(eps, min_points),
+        }
+    }
+
+    fn core_distance(&self, data: &[Point], point_idx: usize, neighbors: &[usize]) -> Option<f64> {
+        let required = self.dbscan.required_neighbors();
+        if neighbors.len() < required {
+            return None;
+        }
+        if required == 0 {
+            return Some(0.0);
+        }
+
+        let mut distances: Vec<f64> = neighbors
+            .iter()
+            .map(|&i| self.dbscan.metric.distance(&data[i], &data[point_idx]))
+            .collect();
+        sort_distances(&mut distances);
+
+        Some(distances[required - 1])
+    }
+
+    /// Removes the seed with the smallest reachability, an undefined one
+    /// first. Reachabilities are ordered by `total_cmp`; ties go to the seed
+    /// queued first.
+    fn pop_closest_seed(seeds: &mut Vec<usize>, reachability: &[Option<f64>]) -> usize {
+        let key = |idx: usize| reachability[idx].unwrap_or(f64::NEG_INFINITY);
+        let mut best = 0;
+        for (pos, &idx) in seeds.iter().enumerate() {
+            if key(idx).total_cmp(&key(seeds[best])).is_lt() {
+                best = pos;
+            }
+        }
+        seeds.remove(best)
+    }
+
+    /// Produces the OPTICS cluster ordering. Expansions are started from core
+    /// points before any leftover non-core point, so border points are always
+    /// reached through a core point and `extract_dbscan` at `eps` reproduces
+    /// `DBSCAN::fit`. Panics if a point has a NaN or infinite coordinate.
+    pub fn fit(&self, data: &[Point]) -> Vec<OpticsEntry> {
+        assert_finite(data);
+        let n = data.len();
+        let neighborhoods: Vec<Vec<usize>> =
+            (0..n).map(|i| self.dbscan.region_query(data, i)).collect();
+        let core_distances: Vec<Option<f64>> = (0..n)
+            .map(|i| self.core_distance(data, i, &neighborhoods[i]))
+            .collect();
+
+        let mut processed = vec![false; n];
+        let mut reachability: Vec<Option<f64>> = vec![None; n];
+        let mut ordering = Vec::with_capacity(n);
+
+        let starts = (0..n)
+            .filter(|&i| core_distances[i].is_some())
+            .chain((0..n).filter(|&i| core_distances[i].is_none()));
+
+        for start in starts {
+            if processed[start] {
+                continue;
+            }
+
+            let mut seeds = vec![start];
+            while !seeds.is_empty() {
+                let current = Self::pop_closest_seed(&mut seeds, &reachability);
+                processed[current] = true;
+
+                let core_distance = core_distances[current];
+
+                ordering.push(OpticsEntry {
+                    index: current,
+                    core_distance,
+                    reachability: reachability[current],
+                });
+
+                let Some(core_distance) = core_distance else {
+                    continue;
+                };
+
+                for &neighbor_idx in &neighborhoods[current] {
+                    if processed[neighbor_idx] {
+                        continue;
+                    }
+
+                    let new_reachability =
+                        core_distance.max(self.dbscan.metric.distance(&data[current], &data[neighbor_idx]));
+                    match reachability[neighbor_idx] {
+                        None => {
+                            reachability[neighbor_idx] = Some(new_reachability);
+                            seeds.push(neighbor_idx);
+                        }
+                        Some(old) if new_reachability.total_cmp(&old).is_lt() => {
+                            reachability[neighbor_idx] = Some(new_reachability);
+                        }
+                        Some(_) => {}
+                    }
+                }
+            }
+        }
+
+        ordering
+    }
+}
+
+/// Derives a DBSCAN clustering for any `eps_prime <= eps` from an OPTICS
+/// ordering, using the same 1-based cluster ids and `None` for noise as
+/// `DBSCAN::fit`. As with the original ExtractDBSCAN procedure, border points
+/// may be reported as noise when `eps_prime` is strictly below `eps`.
+pub fn extract_dbscan(ordering: &[OpticsEntry], eps_prime: f64) -> Vec<Option<usize>> {
+    let mut clusters = vec![None; ordering.len()];
+    let mut cluster_id = 0;
+
+    for entry in ordering {
+        let reachable = matches!(entry.reachability, Some(r) if r <= eps_prime);
+
+        if !reachable {
+            if matches!(entry.core_distance, Some(c) if c <= eps_prime) {
+                cluster_id += 1;
+                clusters[entry.index] = Some(cluster_id);
+            }
+        } else if cluster_id > 0 {
+            clusters[entry.index] = Some(cluster_id);
+        }
+    }
+
+    clusters
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn closest_seed_is_undefined_first_then_smallest_and_nan_last() {
+        let reachability = [Some(f64::NAN), Some(2.0), None, Some(1.0)];
+        let mut seeds = vec![0, 1, 2, 3];
+
+        let popped: Vec<usize> = (0..4).map(|_| Optics::pop_closest_seed(&mut seeds, &reachability)).collect();
+        assert_eq!(popped, vec![2, 3, 1, 0]);
+    }
+
+    #[test]
+    fn extracting_at_eps_reproduces_dbscan() {
+        let data: Vec<Point> = [
+            (1.0, 1.0),
+            (1.0, 8.0),
+            (2.0, 2.0),
+            (2.0, 5.0),
+            (3.0, 1.0),
+            (4.0, 3.0),
+            (5.0, 2.0),
+            (6.0, 1.0),
+            (6.0, 8.0),
+            (8.0, 6.0),
+        ]
+        .into_iter()
+        .map(Point::from)
+        .collect();
+
+        for eps in [1.5, 2.0, 2.5, 3.0] {
+            let ordering = Optics::new(eps, 2).fit(&data);
+            assert_eq!(extract_dbscan(&ordering, eps), DBSCAN::new(eps, 2).fit(&data).0, "eps = {}", eps);
+        }
+    }
+}
@@ -1,7 +1,20 @@
 use std::cell::RefCell;
-use std::collections::HashMap;
-use std::rc::Rc;
+use std::cmp::Reverse;
+use std::collections::{HashMap, HashSet};
+use std::error::Error;
 use std::fmt;
+use std::fs;
+use std::rc::Rc;
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use common::cli::{self, Args};
+use common::{
+    augment_with_ancestors, dedup_transactions, filter_redundant, is_exact, read_transactions_json,
+    rules_to_json, top_rules, value_score, ConsoleReporter, ItemConstraints, RankBy, Reporter, Rule,
+    Silent,
+};
 
 type Transaction = Vec<char>;
 type Support = usize;
@@ -35,7 +48,7 @@ impl FPNode {
 impl Clone for FPNode {
     fn clone(&self) -> Self {
         FPNode {
-            item: self.item.clone(),
+            item: self.item,
             count: self.count,
             parent: self.parent.clone(),
             children: self.children.clone(),
@@ -61,6 +74,18 @@ impl HeaderTableEntry {
 struct FPTree {
     root: Rc<RefCell<FPNode>>,
     header_table: HashMap<char, HeaderTableEntry>,
+    /// Position of each item in the order set by `build_with_order`; empty
+    /// for the default descending-support order.
+    item_order: HashMap<char, usize>,
+    /// Rank of each header item in the path order, fixed by `freeze_order`
+    /// when the tree is built. `insert_transaction` and `remove_transaction`
+    /// change the supports but keep this order, so they follow the paths
+    /// already in the tree.
+    item_rank: HashMap<char, usize>,
+    /// Most conditional trees mining may nest, guarding the stack on dense
+    /// data. When the cap is hit, itemsets with more than `max_depth + 1`
+    /// items are omitted; `None` searches without limit.
+    max_depth: Option<usize>,
 }
 
 impl fmt::Display for FPTree {
@@ -101,6 +126,9 @@ impl FPTree {
         FPTree {
             root: Rc::new(RefCell::new(FPNode::new(None, None))),
             header_table: HashMap::new(),
+            item_order: HashMap::new(),
+            item_rank: HashMap::new(),
+            max_depth: None,
         }
     }
 
@@ -126,13 +154,43 @@ impl FPTree {
         Ok(())
     }
 
+    /// Number of nodes in the tree, the root included.
+    fn node_count(&self) -> usize {
+        let mut count = 0;
+        let mut stack = vec![Rc::clone(&self.root)];
+        while let Some(node) = stack.pop() {
+            count += 1;
+            stack.extend(node.borrow().children.values().cloned());
+        }
+        count
+    }
+
+    /// Rough heap footprint of the nodes: each `Rc<RefCell<FPNode>>`
+    /// allocation plus the slots and control bytes of its children map.
+    /// Header-table and allocator overhead are not counted.
+    fn estimated_bytes(&self) -> usize {
+        let node_bytes = 2 * size_of::<usize>() + size_of::<RefCell<FPNode>>();
+        let slot_bytes = size_of::<(char, Rc<RefCell<FPNode>>)>() + 1;
+
+        let mut bytes = 0;
+        let mut stack = vec![Rc::clone(&self.root)];
+        while let Some(node) = stack.pop() {
+            let node = node.borrow();
+            bytes += node_bytes + node.children.capacity() * slot_bytes;
+            stack.extend(node.children.values().cloned());
+        }
+        bytes
+    }
+
     fn add_transaction(&mut self, transaction: Transaction, count: usize) {
         let mut current_node = Rc::clone(&self.root);
 
         for item in transaction {
             let child: Rc<RefCell<FPNode>> = {
                 let mut node = current_node.borrow_mut();
-                if !node.children.contains_key(&item) {
+                if let Some(existing) = node.children.get(&item) {
+                    Rc::clone(existing)
+                } else {
                     let new_child = Rc::new(RefCell::new(FPNode::new(
                         Some(item),
                         Some(Rc::clone(&current_node)),
@@ -140,20 +198,18 @@ impl FPTree {
                     node.children.insert(item, Rc::clone(&new_child));
 
                     let header_entry = self.header_table.get_mut(&item).unwrap();
-                    if header_entry.head.is_none() {
-                        header_entry.head = Some(Rc::clone(&new_child));
-                    } else {
-                        let mut current = Rc::clone(header_entry.head.as_ref().unwrap());
+                    if let Some(head) = &header_entry.head {
+                        let mut current = Rc::clone(head);
                         while current.borrow().node_link.is_some() {
                             let next = Rc::clone(current.borrow().node_link.as_ref().unwrap());
                             current = next;
                         }
                         current.borrow_mut().node_link = Some(Rc::clone(&new_child));
+                    } else {
+                        header_entry.head = Some(Rc::clone(&new_child));
                     }
 
-                    Rc::clone(&new_child)
-                } else {
-                    Rc::clone(node.children.get(&item).unwrap())
+                    new_child
                 }
             };
 
@@ -163,10 +219,41 @@ impl FPTree {
     }
 
     fn build(&mut self, transactions: &[Transaction], min_support: usize) {
+        self.item_order.clear();
+        self.build_counted(transactions.iter().map(|transaction| (transaction, 1)), min_support);
+    }
+
+    /// Builds the tree from pre-aggregated transactions, where each
+    /// transaction occurred `count` times. Equivalent to `build` on the
+    /// expanded dataset without materializing the duplicates.
+    fn build_weighted(&mut self, transactions: &[(Transaction, usize)], min_support: usize) {
+        self.item_order.clear();
+        self.build_counted(
+            transactions.iter().map(|(transaction, count)| (transaction, *count)),
+            min_support,
+        );
+    }
+
+    /// `build` with a fixed path order, e.g. alphabetical to match a textbook
+    /// example: items listed in `order` come first, in that order, and the
+    /// rest follow by descending support. The tree's shape changes but the
+    /// mined frequent itemsets do not.
+    fn build_with_order(&mut self, transactions: &[Transaction], min_support: usize, order: &[char]) {
+        self.item_order.clear();
+        for (position, item) in order.iter().enumerate() {
+            self.item_order.entry(*item).or_insert(position);
+        }
+        self.build_counted(transactions.iter().map(|transaction| (transaction, 1)), min_support);
+    }
+
+    fn build_counted<'a, T>(&mut self, transactions: T, min_support: usize)
+    where
+        T: Iterator<Item = (&'a Transaction, usize)> + Clone,
+    {
         let mut item_counts: ItemSupport = HashMap::new();
-        for transaction in transactions {
+        for (transaction, count) in transactions.clone() {
             for item in transaction {
-                *item_counts.entry(*item).or_insert(0) += 1;
+                *item_counts.entry(*item).or_insert(0) += count;
             }
         }
 
@@ -177,51 +264,194 @@ impl FPTree {
                     .insert(*item, HeaderTableEntry::new(*count));
             }
         }
+        self.freeze_order();
 
-        for transaction in transactions {
-            let mut filtered_transaction: Vec<(char, usize)> = transaction
-                .iter()
-                .filter_map(|item| {
-                    if let Some(entry) = self.header_table.get(item) {
-                        Some((*item, entry.support))
-                    } else {
-                        None
-                    }
-                })
-                .collect();
+        for (transaction, count) in transactions {
+            let sorted_items = self.order_transaction(transaction);
+            if !sorted_items.is_empty() {
+                self.add_transaction(sorted_items, count);
+            }
+        }
+    }
+
+    /// Ranks the header items by descending support, after any items placed
+    /// by `build_with_order`. Items of equal support are ordered by item, so
+    /// the tree does not depend on input order. Called once the header table
+    /// is filled.
+    fn freeze_order(&mut self) {
+        let mut items: Vec<(char, Support)> = self
+            .header_table
+            .iter()
+            .map(|(item, entry)| (*item, entry.support))
+            .collect();
+        items.sort_by_key(|&(item, support)| {
+            let position = self.item_order.get(&item).copied().unwrap_or(usize::MAX);
+            (position, Reverse(support), item)
+        });
+        self.item_rank = items
+            .into_iter()
+            .enumerate()
+            .map(|(rank, (item, _))| (item, rank))
+            .collect();
+    }
+
+    /// Drops items missing from the header table and orders the rest by
+    /// their frozen rank, which is the path order used inside the tree.
+    fn order_transaction(&self, transaction: &[char]) -> Transaction {
+        let mut ranked: Vec<(usize, char)> = transaction
+            .iter()
+            .filter(|item| self.header_table.contains_key(item))
+            .filter_map(|item| self.item_rank.get(item).map(|&rank| (rank, *item)))
+            .collect();
+        ranked.sort_unstable();
+        ranked.into_iter().map(|(_, item)| item).collect()
+    }
+
+    /// Adds one more occurrence of `transaction` to a built tree, counting it
+    /// in the header-table supports. Items not in the header table are
+    /// ignored. The path follows the order frozen at build time.
+    fn insert_transaction(&mut self, transaction: &[char]) {
+        let sorted_items = self.order_transaction(transaction);
+        for item in &sorted_items {
+            if let Some(entry) = self.header_table.get_mut(item) {
+                entry.support += 1;
+            }
+        }
+        if !sorted_items.is_empty() {
+            self.add_transaction(sorted_items, 1);
+        }
+    }
+
+    /// Removes one occurrence of `transaction` for sliding-window mining. The
+    /// transaction is ordered by the rank frozen at build time and its path
+    /// decremented;
+    /// nodes left with a zero count and no children are unlinked from their
+    /// parent and from the header table's node-link chain, and header entries
+    /// whose support drops to zero are removed. Fails, leaving the tree
+    /// unchanged, when the transaction's path is not in the tree.
+    fn remove_transaction(&mut self, transaction: &[char]) -> Result<(), String> {
+        let sorted_items = self.order_transaction(transaction);
+
+        let mut path = Vec::with_capacity(sorted_items.len());
+        let mut current = Rc::clone(&self.root);
+        for item in &sorted_items {
+            let child = match current.borrow().children.get(item) {
+                Some(child) if child.borrow().count > 0 => Rc::clone(child),
+                _ => return Err(format!("transaction {:?} has no path in the tree", transaction)),
+            };
+            path.push(Rc::clone(&child));
+            current = child;
+        }
 
-            filtered_transaction.sort_by(|a, b| b.1.cmp(&a.1));
+        for node in path.iter().rev() {
+            let item = node.borrow().item.unwrap();
+            node.borrow_mut().count -= 1;
 
-            if !filtered_transaction.is_empty() {
-                let sorted_items: Transaction = filtered_transaction
-                    .into_iter()
-                    .map(|(item, _)| item)
-                    .collect();
+            if let Some(entry) = self.header_table.get_mut(&item) {
+                entry.support -= 1;
+            }
 
-                self.add_transaction(sorted_items, 1);
+            let (count, has_children) = {
+                let node = node.borrow();
+                (node.count, !node.children.is_empty())
+            };
+            if count == 0 && !has_children {
+                self.unlink(node);
+            }
+
+            if self.header_table.get(&item).is_some_and(|entry| entry.support == 0) {
+                self.header_table.remove(&item);
             }
         }
+
+        Ok(())
     }
 
-    fn mine(&self, min_support: usize) -> FrequentItemsets {
+    fn unlink(&mut self, node: &Rc<RefCell<FPNode>>) {
+        let (item, parent, next) = {
+            let node = node.borrow();
+            (node.item.unwrap(), node.parent.clone(), node.node_link.clone())
+        };
+
+        if let Some(parent) = parent {
+            parent.borrow_mut().children.remove(&item);
+        }
+
+        let Some(entry) = self.header_table.get_mut(&item) else {
+            return;
+        };
+        if entry.head.as_ref().is_some_and(|head| Rc::ptr_eq(head, node)) {
+            entry.head = next;
+            return;
+        }
+
+        let mut current = entry.head.clone();
+        while let Some(link) = current {
+            let following = link.borrow().node_link.clone();
+            if following.as_ref().is_some_and(|following| Rc::ptr_eq(following, node)) {
+                link.borrow_mut().node_link = next;
+                return;
+            }
+            current = following;
+        }
+    }
+
+    /// Collects all frequent itemsets. With `sorted` they are normalized by
+    /// `sort_itemsets`; otherwise they are returned in mining order.
+    fn mine(&self, min_support: usize, max_len: Option<usize>, sorted: bool) -> FrequentItemsets {
         let mut result = Vec::new();
-        self.fp_growth(Vec::new(), min_support, &mut result);
+        self.mine_with(min_support, max_len, |itemset, support| result.push((itemset.to_vec(), support)));
+        if sorted {
+            sort_itemsets(&mut result);
+        }
         result
     }
 
-    fn fp_growth(&self, prefix: Vec<char>, min_support: usize, result: &mut FrequentItemsets) {
+    /// Calls `f` with each frequent itemset and its support as soon as it is
+    /// found, so itemsets can be processed without collecting them all.
+    /// Itemsets longer than `max_len` are not searched for. Returns `true`
+    /// when `max_depth` cut the search short, i.e. some itemsets were missed.
+    fn mine_with<F: FnMut(&[char], Support)>(
+        &self,
+        min_support: usize,
+        max_len: Option<usize>,
+        mut f: F,
+    ) -> bool {
+        self.fp_growth(Vec::new(), min_support, max_len, 0, &mut f)
+    }
+
+    /// Mines this tree, which is nested `depth` conditional trees below the
+    /// original one. Returns whether `max_depth` stopped any recursion.
+    fn fp_growth<F: FnMut(&[char], Support)>(
+        &self,
+        prefix: Vec<char>,
+        min_support: usize,
+        max_len: Option<usize>,
+        depth: usize,
+        f: &mut F,
+    ) -> bool {
+        let mut truncated = false;
         let mut sorted_items: Vec<(char, Support)> = self
             .header_table
             .iter()
             .map(|(item, entry)| (*item, entry.support))
             .collect();
-        sorted_items.sort_by(|a, b| a.1.cmp(&b.1));
+        sorted_items.sort_by_key(|&(_, support)| support);
 
         for (item, support) in sorted_items {
+            // Supports can fall below the threshold after `remove_transaction`.
+            if support < min_support {
+                continue;
+            }
+
             let mut new_prefix = prefix.clone();
             new_prefix.push(item);
 
-            result.push((new_prefix.clone(), support));
+            f(&new_prefix, support);
+
+            if max_len.is_some_and(|max_len| new_prefix.len() >= max_len) {
+                continue;
+            }
 
             let mut conditional_pattern_base = Vec::new();
             let mut current_node_option = self.header_table.get(&item).unwrap().head.clone();
@@ -235,8 +465,8 @@ impl FPTree {
 
                 while let Some(parent) = parent_option {
                     let parent_node = parent.borrow();
-                    if parent_node.item.is_some() {
-                        path.push(parent_node.item.unwrap());
+                    if let Some(item) = parent_node.item {
+                        path.push(item);
                     }
                     parent_option = parent_node.parent.clone();
                 }
@@ -267,46 +497,53 @@ impl FPTree {
                             .insert(*item, HeaderTableEntry::new(*count));
                     }
                 }
+                conditional_tree.freeze_order();
 
                 for (path, count) in conditional_pattern_base {
-                    let mut filtered_path: Vec<(char, usize)> = path
-                        .iter()
-                        .filter_map(|item| {
-                            if let Some(entry) = conditional_tree.header_table.get(item) {
-                                Some((*item, entry.support))
-                            } else {
-                                None
-                            }
-                        })
-                        .collect();
-
-                    filtered_path.sort_by(|a, b| b.1.cmp(&a.1));
-
-                    if !filtered_path.is_empty() {
-                        let sorted_items: Transaction =
-                            filtered_path.into_iter().map(|(item, _)| item).collect();
-
+                    let sorted_items = conditional_tree.order_transaction(&path);
+                    if !sorted_items.is_empty() {
                         conditional_tree.add_transaction(sorted_items, count);
                     }
                 }
 
-                if !conditional_tree.header_table.is_empty() {
-                    conditional_tree.fp_growth(new_prefix, min_support, result);
+                if conditional_tree.header_table.is_empty() {
+                    continue;
+                }
+                if self.max_depth.is_some_and(|max_depth| depth >= max_depth) {
+                    truncated = true;
+                    continue;
                 }
+                conditional_tree.max_depth = self.max_depth;
+                truncated |= conditional_tree.fp_growth(new_prefix, min_support, max_len, depth + 1, f);
             }
         }
+
+        truncated
     }
 }
 
+/// Builds rules from mined itemsets. Itemsets come out of the tree in mining
+/// order, so supports are keyed by the sorted itemset and every rule is
+/// reported with sorted antecedent and consequent. Rules below `min_lift` are
+/// dropped as well as those below `min_confidence` or rejected by
+/// `constraints`. `item_values`, when given, prices each rule's
+/// `value_score`.
 fn generate_rules(
     frequent_itemsets: &FrequentItemsets,
     min_confidence: f64,
-) -> Vec<(Vec<char>, Vec<char>, f64)> {
+    min_lift: f64,
+    transaction_count: usize,
+    constraints: &ItemConstraints,
+    item_values: Option<&HashMap<char, f64>>,
+) -> Vec<Rule> {
     let mut rules = Vec::new();
+    let n = transaction_count as f64;
 
     let mut support_map: HashMap<Vec<char>, Support> = HashMap::new();
     for (itemset, support) in frequent_itemsets {
-        support_map.insert(itemset.clone(), *support);
+        let mut key = itemset.clone();
+        key.sort();
+        support_map.insert(key, *support);
     }
 
     for (itemset, support) in frequent_itemsets {
@@ -314,7 +551,11 @@ fn generate_rules(
             continue;
         }
 
-        let subsets = generate_all_subsets(itemset);
+        let mut itemset = itemset.clone();
+        itemset.sort();
+        let itemset_support = *support as f64;
+
+        let subsets = generate_all_subsets(&itemset);
 
         for subset in &subsets {
             if subset.is_empty() || subset.len() == itemset.len() {
@@ -327,19 +568,50 @@ fn generate_rules(
                 .cloned()
                 .collect();
 
-            if consequent.is_empty() {
+            if consequent.is_empty() || !constraints.allows(subset, &consequent) {
                 continue;
             }
 
-            let subset_support = *support_map.get(subset).unwrap_or(&0);
-            if subset_support == 0 {
+            let subset_support = *support_map.get(subset).unwrap_or(&0) as f64;
+            if subset_support == 0.0 {
                 continue;
             }
 
-            let confidence = *support as f64 / subset_support as f64;
+            let confidence = itemset_support / subset_support;
 
             if confidence >= min_confidence {
-                rules.push((subset.clone(), consequent, confidence));
+                let consequent_support = *support_map.get(&consequent).unwrap_or(&0) as f64;
+                let support = itemset_support / n;
+                let antecedent_ratio = subset_support / n;
+                let consequent_ratio = consequent_support / n;
+
+                let lift = confidence / consequent_ratio;
+                if lift < min_lift {
+                    continue;
+                }
+
+                let exact = is_exact(confidence);
+                let conviction = if exact {
+                    f64::INFINITY
+                } else {
+                    (1.0 - consequent_ratio) / (1.0 - confidence)
+                };
+
+                let value = value_score(subset, &consequent, support, item_values);
+                rules.push(Rule {
+                    antecedent: subset.clone(),
+                    consequent,
+                    support,
+                    antecedent_support: subset_support as usize,
+                    consequent_support: consequent_support as usize,
+                    confidence,
+                    lift,
+                    leverage: support - antecedent_ratio * consequent_ratio,
+                    conviction,
+                    exact,
+                    value_score: value,
+                    negated_consequent: false,
+                });
             }
         }
     }
@@ -347,15 +619,56 @@ fn generate_rules(
     rules
 }
 
+/// "Customers who bought `given` also bought": scores every item `y` that
+/// forms a frequent itemset with all of `given` by the conditional support
+/// `support(given + y) / support(given)` and returns the best `top_n`,
+/// highest score first and ties by item. Items in `given` are never
+/// recommended. Empty when `given` is empty or not itself frequent.
+fn recommend(frequent: &FrequentItemsets, given: &[char], top_n: usize) -> Vec<(char, f64)> {
+    let given: HashSet<char> = given.iter().copied().collect();
+    let covers_given = |itemset: &[char]| given.iter().all(|item| itemset.contains(item));
+
+    let given_support = frequent
+        .iter()
+        .find(|(itemset, _)| itemset.len() == given.len() && covers_given(itemset))
+        .map(|(_, support)| *support);
+    let Some(given_support) = given_support.filter(|_| !given.is_empty()) else {
+        return Vec::new();
+    };
+
+    let mut scores: Vec<(char, f64)> = frequent
+        .iter()
+        .filter(|(itemset, _)| itemset.len() == given.len() + 1 && covers_given(itemset))
+        .filter_map(|(itemset, support)| {
+            let item = itemset.iter().find(|item| !given.contains(item))?;
+            Some((*item, *support as f64 / given_support as f64))
+        })
+        .collect();
+    scores.sort_by(|a, b| b.1.total_cmp(&a.1).then(a.0.cmp(&b.0)));
+    scores.truncate(top_n);
+    scores
+}
+
+/// Sorts the items of every itemset, then orders itemsets by length and
+/// lexicographically by contents, dropping exact `(itemset, support)`
+/// duplicates. Gives a stable output regardless of mining order.
+fn sort_itemsets(itemsets: &mut FrequentItemsets) {
+    for (itemset, _) in itemsets.iter_mut() {
+        itemset.sort();
+    }
+    itemsets.sort_by(|(a, _), (b, _)| a.len().cmp(&b.len()).then_with(|| a.cmp(b)));
+    itemsets.dedup();
+}
+
 fn generate_all_subsets(itemset: &[char]) -> Vec<Vec<char>> {
     let n = itemset.len();
     let mut result = Vec::new();
 
     for mask in 0..(1 << n) {
         let mut subset = Vec::new();
-        for i in 0..n {
+        for (i, &item) in itemset.iter().enumerate() {
             if (mask >> i) & 1 == 1 {
-                subset.push(itemset[i]);
+                subset.push(item);
             }
         }
         result.push(subset);
@@ -364,40 +677,161 @@ fn generate_all_subsets(itemset: &[char]) -> Vec<Vec<char>> {
     result
 }
 
+/// Mines itemsets and rules. Transactions are normalized with
+/// `dedup_transactions` first, so repeated items within a transaction count
+/// once, as in Apriori. The built tree is passed to `reporter`, which also
+/// hears if `max_depth` (see `FPTree::max_depth`) omitted long itemsets.
 fn fp_growth(
     transactions: &[Vec<char>],
     min_support: f64,
     min_confidence: f64,
-) -> (FrequentItemsets, Vec<(Vec<char>, Vec<char>, f64)>) {
+    min_lift: f64,
+    max_len: Option<usize>,
+    max_depth: Option<usize>,
+    reporter: &mut dyn Reporter,
+) -> (FrequentItemsets, Vec<Rule>) {
+    let mut transactions = transactions.to_vec();
+    dedup_transactions(&mut transactions);
+    let transactions = transactions.as_slice();
+
     let min_support = (min_support * transactions.len() as f64).ceil() as usize;
 
     let mut fp_tree = FPTree::new();
     fp_tree.build(transactions, min_support);
+    fp_tree.max_depth = max_depth;
 
-    println!("{}", fp_tree);
+    reporter.on_tree_built(&fp_tree);
 
-    let frequent_itemsets = fp_tree.mine(min_support);
+    let mut frequent_itemsets = Vec::new();
+    let truncated = fp_tree.mine_with(min_support, max_len, |itemset, support| {
+        frequent_itemsets.push((itemset.to_vec(), support));
+    });
+    if let (true, Some(max_depth)) = (truncated, max_depth) {
+        reporter.on_depth_limit(max_depth);
+    }
+    sort_itemsets(&mut frequent_itemsets);
 
-    let rules = generate_rules(&frequent_itemsets, min_confidence);
+    let rules = generate_rules(
+        &frequent_itemsets,
+        min_confidence,
+        min_lift,
+        transactions.len(),
+        &ItemConstraints::default(),
+        None,
+    );
 
     (frequent_itemsets, rules)
 }
 
-fn main() {
-    let transactions: Vec<Vec<char>> = vec![
-        vec!['a', 'b', 'c', 'd'],
-        vec!['b', 'c', 'd'],
-        vec!['a', 'e', 'f', 'g', 'h'],
-        vec!['b', 'c', 'd', 'e', 'g', 'j'],
-        vec!['b', 'c', 'd', 'e', 'f'],
-        vec!['a', 'f', 'g'],
-        vec!['a', 'i', 'j'],
-        vec!['a', 'b', 'e', 'h'],
-        vec!['f', 'g', 'h', 'i', 'j'],
-        vec!['e', 'f', 'h'],
-    ];
+/// Approximate `fp_growth` for very large inputs: mines a uniform random
+/// sample of `sample_fraction` of the transactions, drawn without replacement
+/// from a `StdRng` seeded with `seed`. `min_support` stays relative, so the
+/// threshold scales with the sample, and each itemset's support count is
+/// extrapolated to the full dataset by `transactions.len() / sample size`.
+/// Itemsets near the threshold may be missed or spuriously included, and the
+/// supports are estimates; with `sample_fraction = 1.0` the result is exact.
+/// Rules are computed on the sample without a lift threshold.
+fn fp_growth_sampled(
+    transactions: &[Vec<char>],
+    sample_fraction: f64,
+    min_support: f64,
+    min_confidence: f64,
+    seed: u64,
+) -> (FrequentItemsets, Vec<Rule>) {
+    assert!(
+        sample_fraction > 0.0 && sample_fraction <= 1.0,
+        "sample_fraction must be in (0, 1], got {}",
+        sample_fraction
+    );
+
+    let n = transactions.len();
+    let sample_size = ((n as f64 * sample_fraction).round() as usize).clamp(n.min(1), n);
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut indices = rand::seq::index::sample(&mut rng, n, sample_size).into_vec();
+    indices.sort_unstable();
+    let sample: Vec<Vec<char>> = indices.iter().map(|&i| transactions[i].clone()).collect();
+
+    let (mut itemsets, rules) = fp_growth(&sample, min_support, min_confidence, 0.0, None, None, &mut Silent);
+    let scale = n as f64 / sample_size.max(1) as f64;
+    for (_, support) in itemsets.iter_mut() {
+        *support = (*support as f64 * scale).round() as Support;
+    }
 
-    let (frequent_itemsets, rules) = fp_growth(&transactions, 0.4, 0.75);
+    (itemsets, rules)
+}
+
+/// Keeps a uniform random sample of at most `capacity` transactions from a
+/// stream of unknown length in bounded memory (Vitter's Algorithm R), so
+/// `fp_growth` can be run on the reservoir. The draws come from a `StdRng`
+/// seeded with `seed`. A stream no longer than `capacity` is kept whole and
+/// in order.
+fn reservoir_sample<I: Iterator<Item = Vec<char>>>(stream: I, capacity: usize, seed: u64) -> Vec<Vec<char>> {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut reservoir = Vec::with_capacity(capacity);
+
+    for (seen, transaction) in stream.enumerate() {
+        if seen < capacity {
+            reservoir.push(transaction);
+        } else {
+            let slot = rng.gen_range(0..=seen);
+            if slot < capacity {
+                reservoir[slot] = transaction;
+            }
+        }
+    }
+
+    reservoir
+}
+
+/// Narrows string transactions (as read from JSON) to the single-character
+/// items this miner works with.
+fn to_char_transactions(transactions: Vec<Vec<String>>) -> Result<Vec<Transaction>, String> {
+    transactions
+        .into_iter()
+        .map(|transaction| {
+            transaction
+                .into_iter()
+                .map(|item| {
+                    let mut chars = item.chars();
+                    match (chars.next(), chars.next()) {
+                        (Some(c), None) => Ok(c),
+                        _ => Err(format!("item {:?} is not a single character", item)),
+                    }
+                })
+                .collect()
+        })
+        .collect()
+}
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let args = Args::from_env_or_exit(&[cli::INPUT, cli::MIN_SUPPORT, cli::MIN_CONFIDENCE]);
+    let min_support = args.value_or_exit::<f64>(cli::MIN_SUPPORT).unwrap_or(0.4);
+    let min_confidence = args.value_or_exit::<f64>(cli::MIN_CONFIDENCE).unwrap_or(0.75);
+    let transactions: Vec<Vec<char>> = match args.value_or_exit::<String>(cli::INPUT) {
+        Some(path) => to_char_transactions(read_transactions_json(&fs::read_to_string(path)?)?)?,
+        None => vec![
+            vec!['a', 'b', 'c', 'd'],
+            vec!['b', 'c', 'd'],
+            vec!['a', 'e', 'f', 'g', 'h'],
+            vec!['b', 'c', 'd', 'e', 'g', 'j'],
+            vec!['b', 'c', 'd', 'e', 'f'],
+            vec!['a', 'f', 'g'],
+            vec!['a', 'i', 'j'],
+            vec!['a', 'b', 'e', 'h'],
+            vec!['f', 'g', 'h', 'i', 'j'],
+            vec!['e', 'f', 'h'],
+        ],
+    };
+
+    let (frequent_itemsets, rules) = fp_growth(
+        &transactions,
+        min_support,
+        min_confidence,
+        1.0,
+        None,
+        None,
+        &mut ConsoleReporter,
+    );
 
     println!("Frequent Itemsets (with support):");
     for (i, (itemset, support)) in frequent_itemsets.iter().enumerate() {
@@ -413,13 +847,611 @@ fn main() {
     }
 
     println!("\nAssociation Rules:");
-    for (i, (antecedent, consequent, confidence)) in rules.iter().enumerate() {
+    for (i, rule) in rules.iter().enumerate() {
         println!(
-            "{}. {:?} => {:?} (confidence: {:.2}%)",
+            "{}. {:?} => {:?} (confidence: {:.2}%, lift: {:.2})",
             i + 1,
-            antecedent,
-            consequent,
-            confidence * 100.0
+            rule.antecedent,
+            rule.consequent,
+            rule.confidence * 100.0,
+            rule.lift
+        );
+    }
+
+    println!("\nExact rules (confidence 100%):");
+    for rule in rules.iter().filter(|r| r.exact) {
+        println!("{:?} => {:?}", rule.antecedent, rule.consequent);
+    }
+
+    let general_rules = filter_redundant(rules.clone());
+    println!(
+        "\nNon-redundant rules ({} of {} kept):",
+        general_rules.len(),
+        rules.len()
+    );
+    for rule in &general_rules {
+        println!(
+            "{:?} => {:?} (confidence: {:.2}%)",
+            rule.antecedent,
+            rule.consequent,
+            rule.confidence * 100.0
         );
     }
+
+    println!("\nRecommendations for a basket with 'c':");
+    for (item, score) in recommend(&frequent_itemsets, &['c'], 3) {
+        println!("{:?} (conditional support: {:.2}%)", item, score * 100.0);
+    }
+
+    println!("\nRules as JSON:\n{}", rules_to_json(&rules));
+
+    println!("\nRules predicting 'd' without 'c' in the antecedent:");
+    let constraints = ItemConstraints {
+        require_consequent: Some(HashSet::from(['d'])),
+        forbid_antecedent: Some(HashSet::from(['c'])),
+        item_parent: None,
+    };
+    for rule in generate_rules(&frequent_itemsets, 0.5, 0.0, transactions.len(), &constraints, None) {
+        println!(
+            "{:?} => {:?} (confidence: {:.2}%)",
+            rule.antecedent,
+            rule.consequent,
+            rule.confidence * 100.0
+        );
+    }
+
+    let (low_support_itemsets, _) = fp_growth(&transactions, 0.2, 0.5, 0.0, None, None, &mut Silent);
+    let item_values = HashMap::from([('a', 1.0), ('b', 1.0), ('c', 1.0), ('d', 1.0), ('h', 40.0)]);
+    let valued_rules = generate_rules(
+        &low_support_itemsets,
+        0.5,
+        0.0,
+        transactions.len(),
+        &ItemConstraints::default(),
+        Some(&item_values),
+    );
+    for (label, by) in [("support", RankBy::Support), ("value", RankBy::Value)] {
+        println!("\nTop 3 rules by {}:", label);
+        for rule in top_rules(&valued_rules, 3, by) {
+            println!(
+                "{:?} => {:?} (support: {:.1}%, value score: {:.2})",
+                rule.antecedent,
+                rule.consequent,
+                rule.support * 100.0,
+                rule.value_score
+            );
+        }
+    }
+
+    let min_count = (min_support * transactions.len() as f64).ceil() as usize;
+    let mut alphabetical = FPTree::new();
+    alphabetical.build_with_order(&transactions, min_count, &['a', 'b', 'c', 'd', 'e', 'f', 'g', 'h']);
+    println!("\nTree with alphabetical item order:\n{}", alphabetical);
+
+    let item_parent = HashMap::from([('a', 'P'), ('b', 'P'), ('c', 'Q'), ('d', 'Q')]);
+    let mut augmented = transactions.clone();
+    augment_with_ancestors(&mut augmented, &item_parent);
+    let (category_itemsets, _) = fp_growth(&augmented, 0.4, 0.75, 0.0, None, None, &mut Silent);
+    println!("\nFrequent pairs with categories P = {{a, b}} and Q = {{c, d}}:");
+    for (itemset, support) in category_itemsets.iter().filter(|(itemset, _)| itemset.len() == 2) {
+        if itemset.iter().any(|item| item.is_uppercase()) {
+            println!("{:?} (support: {})", itemset, support);
+        }
+    }
+    let taxonomy = ItemConstraints {
+        item_parent: Some(item_parent),
+        ..ItemConstraints::default()
+    };
+    let n = augmented.len();
+    let unrestricted = generate_rules(&category_itemsets, 0.75, 0.0, n, &ItemConstraints::default(), None);
+    let generalized = generate_rules(&category_itemsets, 0.75, 0.0, n, &taxonomy, None);
+    println!(
+        "Rules between an item and its own category: {} of {} dropped",
+        unrestricted.len() - generalized.len(),
+        unrestricted.len()
+    );
+
+    println!("\nStreaming itemsets with at least two items:");
+    let mut tree = FPTree::new();
+    let min_count = (0.4 * transactions.len() as f64).ceil() as usize;
+    tree.build(&transactions, min_count);
+    tree.mine_with(min_count, None, |itemset, support| {
+        if itemset.len() >= 2 {
+            println!("{:?} (support: {})", itemset, support);
+        }
+    });
+    println!("Tree size: {} nodes, about {} bytes", tree.node_count(), tree.estimated_bytes());
+
+    let weighted: Vec<(Transaction, usize)> = vec![
+        (vec!['a', 'b', 'c'], 3),
+        (vec!['b', 'c'], 2),
+        (vec!['a', 'd'], 1),
+    ];
+    let mut weighted_tree = FPTree::new();
+    weighted_tree.build_weighted(&weighted, 2);
+    println!("\nWeighted tree from {} unique transactions:", weighted.len());
+    println!("{}", weighted_tree);
+
+    let mut window = FPTree::new();
+    window.build(&transactions, min_count);
+    window.remove_transaction(&transactions[0])?;
+    window.insert_transaction(&['b', 'c', 'd', 'e']);
+    println!("\nItemsets after sliding the window past {:?} to [b, c, d, e]:", transactions[0]);
+    for (itemset, support) in window.mine(min_count, None, true) {
+        println!("{:?} (support: {})", itemset, support);
+    }
+
+    println!("\nMining [a, a, b] and [b]: repeated items count once");
+    let (duplicate_itemsets, _) = fp_growth(
+        &[vec!['a', 'a', 'b'], vec!['b']],
+        0.5,
+        1.0,
+        1.0,
+        None,
+        None,
+        &mut ConsoleReporter,
+    );
+    for (itemset, support) in &duplicate_itemsets {
+        println!("{:?} (support: {})", itemset, support);
+    }
+
+    let (half_itemsets, _) = fp_growth_sampled(&transactions, 0.5, min_support, min_confidence, 42);
+    println!("\nItemsets estimated from a 50% sample:");
+    for (itemset, support) in &half_itemsets {
+        println!("{:?} (estimated support: {})", itemset, support);
+    }
+
+    let stream = transactions.iter().cloned().cycle().take(10 * transactions.len());
+    let reservoir = reservoir_sample(stream, 5, 7);
+    println!(
+        "\nReservoir of {} from a stream of {}: {:?}",
+        reservoir.len(),
+        10 * transactions.len(),
+        reservoir
+    );
+    let (reservoir_itemsets, _) =
+        fp_growth(&reservoir, min_support, min_confidence, 0.0, None, None, &mut Silent);
+    println!("Itemsets mined from the reservoir: {}", reservoir_itemsets.len());
+
+    let dense: Vec<Transaction> = (0..12).map(|_| ('a'..='l').collect()).collect();
+    println!("\nMining 12 identical transactions of 12 items with max_depth = 2:");
+    let (shallow_itemsets, _) = fp_growth(&dense, 0.5, 1.0, 0.0, None, Some(2), &mut ConsoleReporter);
+    println!(
+        "{} itemsets, the longest with {} items (4095 without the cap)",
+        shallow_itemsets.len(),
+        shallow_itemsets.iter().map(|(itemset, _)| itemset.len()).max().unwrap_or(0)
+    );
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_transactions() -> Vec<Transaction> {
+        vec![
+            vec!['a', 'b', 'c', 'd'],
+            vec!['b', 'c', 'd'],
+            vec!['a', 'e', 'f', 'g', 'h'],
+            vec!['b', 'c', 'd', 'e', 'g', 'j'],
+            vec!['b', 'c', 'd', 'e', 'f'],
+            vec!['a', 'f', 'g'],
+            vec!['a', 'i', 'j'],
+            vec!['a', 'b', 'e', 'h'],
+            vec!['f', 'g', 'h', 'i', 'j'],
+            vec!['e', 'f', 'h'],
+        ]
+    }
+
+    /// Every root-to-node path with the node's count, sorted, so trees can be
+    /// compared regardless of node-link order.
+    fn paths(tree: &FPTree) -> Vec<(Vec<char>, usize)> {
+        let mut paths = Vec::new();
+        let mut stack = vec![(Rc::clone(&tree.root), Vec::new())];
+        while let Some((node, prefix)) = stack.pop() {
+            for (item, child) in node.borrow().children.iter() {
+                let mut path = prefix.clone();
+                path.push(*item);
+                paths.push((path.clone(), child.borrow().count));
+                stack.push((Rc::clone(child), path));
+            }
+        }
+        paths.sort();
+        paths
+    }
+
+    #[test]
+    fn item_order_changes_the_tree_but_not_the_itemsets() {
+        let transactions = sample_transactions();
+        let mut alphabetical = FPTree::new();
+        alphabetical.build_with_order(&transactions, 4, &['a', 'b', 'c', 'd', 'e', 'f', 'g', 'h']);
+        let mut reversed = FPTree::new();
+        reversed.build_with_order(&transactions, 4, &['h', 'g', 'f', 'e', 'd', 'c', 'b', 'a']);
+
+        assert_eq!(alphabetical.mine(4, None, true), reversed.mine(4, None, true));
+        assert_ne!(paths(&alphabetical), paths(&reversed));
+    }
+
+    #[test]
+    fn permuted_transactions_build_identical_trees() {
+        let transactions = sample_transactions();
+        let mut permuted: Vec<Transaction> = transactions
+            .iter()
+            .map(|transaction| transaction.iter().rev().cloned().collect())
+            .collect();
+        permuted.reverse();
+
+        let mut tree = FPTree::new();
+        tree.build(&transactions, 4);
+        let mut permuted_tree = FPTree::new();
+        permuted_tree.build(&permuted, 4);
+
+        assert_eq!(paths(&tree), paths(&permuted_tree));
+    }
+
+    #[test]
+    fn insert_then_remove_restores_the_tree() {
+        let mut tree = FPTree::new();
+        tree.build(&sample_transactions(), 4);
+        let before = tree.to_string();
+
+        tree.insert_transaction(&['a', 'b', 'c', 'd']);
+        assert_ne!(tree.to_string(), before);
+        tree.remove_transaction(&['a', 'b', 'c', 'd']).unwrap();
+
+        assert_eq!(tree.to_string(), before);
+    }
+
+    #[test]
+    fn removals_follow_the_build_order_after_supports_change() {
+        // Built with a (4) ahead of b (3); two removals of [a] make b the more
+        // frequent item, but [a, b] still lives on the a -> b path.
+        let transactions = vec![vec!['a', 'b'], vec!['a', 'b'], vec!['a'], vec!['a'], vec!['b']];
+        let mut tree = FPTree::new();
+        tree.build(&transactions, 1);
+
+        tree.remove_transaction(&['a']).unwrap();
+        tree.remove_transaction(&['a']).unwrap();
+        assert!(tree.header_table[&'b'].support > tree.header_table[&'a'].support);
+        tree.remove_transaction(&['a', 'b']).unwrap();
+        tree.insert_transaction(&['b', 'a']);
+
+        assert_eq!(tree.header_table[&'a'].support, 2);
+        assert_eq!(tree.header_table[&'b'].support, 3);
+        assert_eq!(
+            paths(&tree),
+            vec![(vec!['a'], 2), (vec!['a', 'b'], 2), (vec!['b'], 1)]
+        );
+        assert_eq!(
+            tree.mine(1, None, true),
+            vec![(vec!['a'], 2), (vec!['b'], 3), (vec!['a', 'b'], 2)]
+        );
+    }
+
+    /// Records every reporter call instead of printing it.
+    #[derive(Default)]
+    struct Recorder {
+        events: Vec<String>,
+    }
+
+    impl Reporter for Recorder {
+        fn on_tree_built(&mut self, _tree: &dyn fmt::Display) {
+            self.events.push("tree built".to_string());
+        }
+
+        fn on_converged(&mut self, iterations: usize, _centroids: &[common::Point]) {
+            self.events.push(format!("converged after {}", iterations));
+        }
+
+        fn on_depth_limit(&mut self, max_depth: usize) {
+            self.events.push(format!("depth limit {}", max_depth));
+        }
+    }
+
+    #[test]
+    fn depth_limit_is_reported_only_when_it_truncates() {
+        // Five items in every transaction: the longest itemset needs four
+        // nested conditional trees.
+        let dense: Vec<Transaction> = (0..6).map(|_| ('a'..='e').collect()).collect();
+
+        for (max_depth, expected) in [
+            (None, vec!["tree built"]),
+            (Some(4), vec!["tree built"]),
+            (Some(3), vec!["tree built", "depth limit 3"]),
+        ] {
+            let mut recorder = Recorder::default();
+            let (itemsets, _) = fp_growth(&dense, 0.5, 1.0, 0.0, None, max_depth, &mut recorder);
+            assert_eq!(recorder.events, expected, "max_depth = {:?}", max_depth);
+            assert_eq!(itemsets.len() == 31, expected.len() == 1, "max_depth = {:?}", max_depth);
+        }
+    }
+
+    #[test]
+    fn the_silent_reporter_does_not_change_the_result() {
+        let transactions = sample_transactions();
+        let mut recorder = Recorder::default();
+
+        let silent = fp_growth(&transactions, 0.4, 0.75, 0.0, None, None, &mut Silent);
+        let recorded = fp_growth(&transactions, 0.4, 0.75, 0.0, None, None, &mut recorder);
+        assert_eq!(silent.0, recorded.0);
+        assert_eq!(recorder.events, vec!["tree built"]);
+    }
+
+    /// Runs the ignored test `name` in a child process and returns its
+    /// stdout; the harness gives a test no way to read its own output.
+    fn stdout_of(name: &str) -> String {
+        let output = std::process::Command::new(std::env::current_exe().unwrap())
+            .args([name, "--exact", "--ignored", "--nocapture"])
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+        String::from_utf8(output.stdout).unwrap()
+    }
+
+    #[test]
+    #[ignore = "run in a child process by the_silent_reporter_prints_nothing"]
+    fn mine_with_the_silent_reporter() {
+        fp_growth(&sample_transactions(), 0.4, 0.75, 0.0, None, Some(0), &mut Silent);
+    }
+
+    #[test]
+    #[ignore = "run in a child process by the_silent_reporter_prints_nothing"]
+    fn mine_with_the_console_reporter() {
+        fp_growth(&sample_transactions(), 0.4, 0.75, 0.0, None, Some(0), &mut ConsoleReporter);
+    }
+
+    #[test]
+    fn the_silent_reporter_prints_nothing() {
+        let console = stdout_of("tests::mine_with_the_console_reporter");
+        assert!(console.contains("FP-Tree Structure:") && console.contains("Stopped at recursion depth 0"));
+
+        let stdout = stdout_of("tests::mine_with_the_silent_reporter");
+        assert!(stdout.contains("1 passed"), "{}", stdout);
+        assert!(!stdout.contains("FP-Tree") && !stdout.contains("Stopped at recursion depth"), "{}", stdout);
+    }
+
+    #[test]
+    fn repeated_items_count_once() {
+        let baskets = vec![vec!['a', 'a', 'b'], vec!['b']];
+        let (itemsets, _) = fp_growth(&baskets, 0.5, 1.0, 1.0, None, None, &mut Silent);
+
+        assert!(itemsets.contains(&(vec!['a'], 1)), "{:?}", itemsets);
+        assert!(itemsets.contains(&(vec!['b'], 2)), "{:?}", itemsets);
+    }
+
+    #[test]
+    fn removing_a_missing_transaction_fails_without_changes() {
+        let mut tree = FPTree::new();
+        tree.build(&[vec!['a', 'b'], vec!['a']], 1);
+        let before = tree.to_string();
+
+        assert!(tree.remove_transaction(&['b']).is_err());
+        assert_eq!(tree.to_string(), before);
+    }
+
+    #[test]
+    fn confidence_is_the_itemset_support_over_the_antecedent_support() {
+        let transactions = sample_transactions();
+        let n = transactions.len() as f64;
+        let (_, rules) = fp_growth(&transactions, 0.2, 0.5, 0.0, None, None, &mut Silent);
+
+        assert!(!rules.is_empty());
+        for rule in &rules {
+            let itemset_support = rule.support * n;
+            assert!((itemset_support / rule.antecedent_support as f64 - rule.confidence).abs() < 1e-9);
+            assert!(rule.consequent_support >= itemset_support.round() as usize);
+        }
+    }
+
+    #[test]
+    fn min_lift_drops_a_confident_rule_into_a_staple_item() {
+        // 'm' is in every transaction, so a => m is exact but has lift 1.
+        let transactions = vec![
+            vec!['a', 'm'],
+            vec!['a', 'm'],
+            vec!['a', 'b', 'c', 'm'],
+            vec!['b', 'c', 'm'],
+            vec!['m'],
+        ];
+        let has_rule = |rules: &[Rule], antecedent: &[char], consequent: &[char]| {
+            rules.iter().any(|r| r.antecedent == antecedent && r.consequent == consequent)
+        };
+
+        let (_, rules) = fp_growth(&transactions, 0.4, 0.9, 1.0, None, None, &mut Silent);
+        assert!(has_rule(&rules, &['a'], &['m']));
+        let (_, lifted) = fp_growth(&transactions, 0.4, 0.9, 1.2, None, None, &mut Silent);
+        assert!(!has_rule(&lifted, &['a'], &['m']));
+        assert!(has_rule(&lifted, &['b'], &['c']));
+        assert!(lifted.iter().all(|rule| rule.lift >= 1.2));
+    }
+
+    #[test]
+    fn max_len_caps_the_itemset_length() {
+        let transactions = sample_transactions();
+        let (uncapped, _) = fp_growth(&transactions, 0.2, 0.5, 0.0, None, None, &mut Silent);
+        let (capped, rules) = fp_growth(&transactions, 0.2, 0.5, 0.0, Some(2), None, &mut Silent);
+
+        assert!(uncapped.iter().any(|(itemset, _)| itemset.len() > 2));
+        assert!(capped.iter().all(|(itemset, _)| itemset.len() <= 2));
+        assert_eq!(capped.len(), uncapped.iter().filter(|(itemset, _)| itemset.len() <= 2).count());
+        assert!(rules.iter().all(|rule| rule.antecedent.len() + rule.consequent.len() <= 2));
+    }
+
+    #[test]
+    fn only_the_deterministic_implication_is_exact() {
+        // Every transaction with 'a' also has 'b'.
+        let transactions = vec![vec!['a', 'b'], vec!['a', 'b', 'c'], vec!['b', 'c'], vec!['c']];
+        let (_, rules) = fp_growth(&transactions, 0.5, 0.0, 0.0, None, None, &mut Silent);
+
+        assert_eq!(rules.len(), 4);
+        let exact: Vec<&Rule> = rules.iter().filter(|rule| rule.exact).collect();
+        assert_eq!(exact.len(), 1);
+        assert_eq!((exact[0].antecedent.as_slice(), exact[0].consequent.as_slice()), (&['a'][..], &['b'][..]));
+        assert_eq!(exact[0].conviction, f64::INFINITY);
+    }
+
+    #[test]
+    fn required_consequent_yields_only_rules_about_it() {
+        let (frequent_itemsets, _) = fp_growth(&sample_transactions(), 0.2, 0.5, 0.0, None, None, &mut Silent);
+        let constraints = ItemConstraints {
+            require_consequent: Some(HashSet::from(['e'])),
+            forbid_antecedent: Some(HashSet::from(['h'])),
+            item_parent: None,
+        };
+        let rules = generate_rules(&frequent_itemsets, 0.5, 0.0, 10, &constraints, None);
+
+        assert!(!rules.is_empty());
+        assert!(rules.iter().all(|rule| rule.consequent == ['e']));
+        assert!(rules.iter().all(|rule| !rule.antecedent.contains(&'h')));
+    }
+
+    #[test]
+    fn categories_are_mined_and_rules_to_own_category_suppressed() {
+        let item_parent = HashMap::from([('a', 'P'), ('b', 'P'), ('c', 'Q'), ('d', 'Q')]);
+        let mut augmented = sample_transactions();
+        augment_with_ancestors(&mut augmented, &item_parent);
+        let (itemsets, _) = fp_growth(&augmented, 0.4, 0.75, 0.0, None, None, &mut Silent);
+
+        let support = |wanted: &[char]| {
+            itemsets.iter().find(|(itemset, _)| itemset == wanted).map(|(_, support)| *support)
+        };
+        assert_eq!(support(&['P']), Some(8));
+        assert_eq!(support(&['Q']), Some(4));
+        assert_eq!(support(&['P', 'Q']), Some(4));
+
+        let taxonomy = ItemConstraints {
+            item_parent: Some(item_parent),
+            ..ItemConstraints::default()
+        };
+        let own_category = |rule: &Rule| {
+            let related = |x: char, y: char| taxonomy.item_parent.as_ref().unwrap().get(&x) == Some(&y);
+            rule.antecedent
+                .iter()
+                .any(|&a| rule.consequent.iter().any(|&c| related(a, c) || related(c, a)))
+        };
+        let unrestricted = generate_rules(&itemsets, 0.75, 0.0, 10, &ItemConstraints::default(), None);
+        let generalized = generate_rules(&itemsets, 0.75, 0.0, 10, &taxonomy, None);
+        assert!(unrestricted.iter().any(own_category));
+        assert!(!generalized.iter().any(own_category));
+        assert_eq!(generalized.len(), unrestricted.iter().filter(|rule| !own_category(rule)).count());
+    }
+
+    #[test]
+    fn the_callback_runs_once_per_mined_itemset() {
+        let mut tree = FPTree::new();
+        tree.build(&sample_transactions(), 2);
+
+        let mut calls = 0;
+        tree.mine_with(2, None, |_, _| calls += 1);
+        assert_eq!(calls, tree.mine(2, None, false).len());
+        assert_eq!(calls, tree.mine(2, None, true).len());
+    }
+
+    #[test]
+    fn weighted_transactions_build_the_expanded_tree() {
+        let weighted: Vec<(Transaction, usize)> = vec![
+            (vec!['a', 'b', 'c'], 3),
+            (vec!['b', 'c'], 2),
+            (vec!['a', 'd'], 1),
+        ];
+        let expanded: Vec<Transaction> = weighted
+            .iter()
+            .flat_map(|(transaction, count)| std::iter::repeat_n(transaction.clone(), *count))
+            .collect();
+
+        let mut weighted_tree = FPTree::new();
+        weighted_tree.build_weighted(&weighted, 2);
+        let mut expanded_tree = FPTree::new();
+        expanded_tree.build(&expanded, 2);
+        assert_eq!(paths(&weighted_tree), paths(&expanded_tree));
+        assert_eq!(weighted_tree.to_string(), expanded_tree.to_string());
+        assert_eq!(weighted_tree.mine(2, None, true), expanded_tree.mine(2, None, true));
+    }
+
+    #[test]
+    fn sorted_itemsets_are_ordered_and_unique() {
+        let mut tree = FPTree::new();
+        tree.build(&sample_transactions(), 2);
+        let itemsets = tree.mine(2, None, true);
+
+        assert!(itemsets.iter().all(|(itemset, _)| itemset.windows(2).all(|w| w[0] < w[1])));
+        assert!(itemsets
+            .windows(2)
+            .all(|w| (w[0].0.len(), &w[0].0) < (w[1].0.len(), &w[1].0)));
+
+        let mut duplicated = vec![(vec!['b', 'a'], 3), (vec!['c'], 4), (vec!['a', 'b'], 3)];
+        sort_itemsets(&mut duplicated);
+        assert_eq!(duplicated, vec![(vec!['c'], 4), (vec!['a', 'b'], 3)]);
+    }
+
+    #[test]
+    fn sampling_every_transaction_is_exact() {
+        let transactions = sample_transactions();
+        let exact = fp_growth(&transactions, 0.4, 0.75, 0.0, None, None, &mut Silent);
+
+        for seed in [0, 42] {
+            assert_eq!(fp_growth_sampled(&transactions, 1.0, 0.4, 0.75, seed), exact);
+        }
+    }
+
+    #[test]
+    fn sampled_supports_are_scaled_to_the_full_dataset() {
+        let transactions: Vec<Transaction> = (0..10).map(|_| vec!['a', 'b']).collect();
+        let (itemsets, _) = fp_growth_sampled(&transactions, 0.5, 0.5, 1.0, 7);
+
+        assert_eq!(itemsets, vec![(vec!['a'], 10), (vec!['b'], 10), (vec!['a', 'b'], 10)]);
+    }
+
+    #[test]
+    fn items_bought_with_c_are_recommended() {
+        let (frequent_itemsets, _) = fp_growth(&sample_transactions(), 0.4, 0.75, 1.0, None, None, &mut Silent);
+
+        // 'b' and 'd' appear in every transaction with 'c'.
+        assert_eq!(recommend(&frequent_itemsets, &['c'], 3), vec![('b', 1.0), ('d', 1.0)]);
+        assert_eq!(recommend(&frequent_itemsets, &['b', 'c'], 3), vec![('d', 1.0)]);
+        assert!(recommend(&frequent_itemsets, &[], 3).is_empty());
+        assert!(recommend(&frequent_itemsets, &['i'], 3).is_empty());
+    }
+
+    #[test]
+    fn node_count_matches_a_manual_traversal() {
+        let mut tree = FPTree::new();
+        tree.build(&sample_transactions(), 4);
+
+        let linked_nodes: usize = tree
+            .header_table
+            .values()
+            .map(|entry| std::iter::successors(entry.head.clone(), |node| node.borrow().node_link.clone()).count())
+            .sum();
+        assert_eq!(tree.node_count(), paths(&tree).len() + 1);
+        assert_eq!(tree.node_count(), linked_nodes + 1);
+        assert!(tree.estimated_bytes() >= tree.node_count() * size_of::<RefCell<FPNode>>());
+        assert_eq!(FPTree::new().node_count(), 1);
+    }
+
+    #[test]
+    fn a_large_reservoir_keeps_the_stream_and_sampling_is_reproducible() {
+        let transactions = sample_transactions();
+        let stream = || transactions.iter().cloned().cycle().take(10 * transactions.len());
+
+        assert_eq!(reservoir_sample(transactions.iter().cloned(), transactions.len(), 7), transactions);
+        assert_eq!(reservoir_sample(transactions.iter().cloned(), 100, 7), transactions);
+
+        let reservoir = reservoir_sample(stream(), 5, 7);
+        assert_eq!(reservoir.len(), 5);
+        assert_eq!(reservoir, reservoir_sample(stream(), 5, 7));
+        assert!(reservoir.iter().all(|t| transactions.contains(t)));
+    }
+
+    #[test]
+    fn a_small_max_depth_returns_only_short_itemsets() {
+        // Mining this uncapped nests 11 conditional trees and yields 4095 itemsets.
+        let dense: Vec<Transaction> = (0..12).map(|_| ('a'..='l').collect()).collect();
+        let (itemsets, rules) = fp_growth(&dense, 0.5, 1.0, 0.0, None, Some(2), &mut Silent);
+
+        assert!(itemsets.iter().all(|(itemset, support)| itemset.len() <= 3 && *support == 12));
+        assert_eq!(itemsets.len(), 12 + 66 + 220);
+        assert!(rules.iter().all(|rule| rule.antecedent.len() + rule.consequent.len() <= 3));
+    }
 }
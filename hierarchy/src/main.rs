@@ -1,20 +1,14 @@
-#[derive(Debug, Clone, Copy)]
-struct Point {
-    x: f64,
-    y: f64,
-}
-
-impl Point {
-    fn new(x: f64, y: f64) -> Self {
-        Point { x, y }
-    }
+use std::collections::{HashMap, HashSet};
+use std::fs::File;
+use std::io::{self, BufReader};
+use std::ops::RangeInclusive;
 
-    fn distance(&self, other: &Point) -> f64 {
-        let dx = self.x - other.x;
-        let dy = self.y - other.y;
-        (dx * dx + dy * dy).sqrt()
-    }
-}
+use common::cli::{self, Args};
+use common::metrics::{bray_curtis, canberra, cluster_sse, silhouette_from_distances};
+use common::{
+    assert_finite, distance_matrix, evaluate, read_points, Clusterer, Distance, Euclidean, Metric,
+    Minkowski, MixedDistance, Point,
+};
 
 #[derive(Debug, Clone)]
 struct Cluster {
@@ -50,20 +44,97 @@ impl Cluster {
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum LinkageMethod {
     Single,
     Complete,
+    /// UPGMA: the mean over all cross-cluster point pairs, so every point
+    /// counts equally and larger clusters pull the distance their way.
     Average,
+    /// WPGMA: the mean of the distances from the two clusters of the last
+    /// merge, so both halves count equally whatever their size.
+    WeightedAverage,
+    /// UPGMC: the Euclidean distance between the weighted centroids of the
+    /// two clusters. Needs the points, not just a distance matrix.
+    Centroid,
+    /// The increase in weighted within-cluster SSE caused by the merge,
+    /// `w_a * w_b / (w_a + w_b) * |c_a - c_b|²` for total weights `w` and
+    /// centroids `c`. Needs the points, not just a distance matrix.
+    Ward,
 }
 
-struct HierarchicalClustering {
-    data: Vec<Point>,
+/// `1 - |a ∩ b| / |a ∪ b|`; two empty sets are at distance 0.
+fn jaccard_distance(a: &HashSet<char>, b: &HashSet<char>) -> f64 {
+    let union = a.union(b).count();
+    if union == 0 {
+        return 0.0;
+    }
+    1.0 - a.intersection(b).count() as f64 / union as f64
+}
+
+/// Agglomerative clustering over a precomputed pairwise distance matrix.
+/// `data` borrows the original points when clustering in a metric space and
+/// is empty when only distances are known (e.g. `from_sets`). The matrix is
+/// computed once, so one instance can be refit under several linkages with
+/// `set_method`. `weights[i]` is the number of observations item `i` stands
+/// for; it is 1 for every item unless set with `set_weights`.
+#[derive(Debug, Clone)]
+struct HierarchicalClustering<'a> {
+    data: &'a [Point],
+    distances: Vec<Vec<f64>>,
+    weights: Vec<f64>,
     method: LinkageMethod,
 }
 
-impl HierarchicalClustering {
-    fn new(data: Vec<Point>, method: LinkageMethod) -> Self {
-        HierarchicalClustering { data, method }
+impl<'a> HierarchicalClustering<'a> {
+    fn new(data: &'a [Point], method: LinkageMethod) -> Self {
+        Self::with_metric(data, method, &Euclidean)
+    }
+
+    fn with_metric<D: Distance>(data: &'a [Point], method: LinkageMethod, metric: &D) -> Self {
+        let distances = distance_matrix(data, metric);
+        let weights = vec![1.0; data.len()];
+        HierarchicalClustering { data, distances, weights, method }
+    }
+
+    /// Clusters items known only through a symmetric distance matrix.
+    fn from_distance_matrix(distances: Vec<Vec<f64>>, method: LinkageMethod) -> Self {
+        HierarchicalClustering {
+            data: &[],
+            weights: vec![1.0; distances.len()],
+            distances,
+            method,
+        }
+    }
+
+    /// Switches the linkage used by later fits, keeping the data and the
+    /// distance matrix.
+    fn set_method(&mut self, method: LinkageMethod) {
+        self.method = method;
+    }
+
+    /// Lets item `i` stand for `weights[i]` observations in average linkage
+    /// and in cluster centroids, and so in centroid and Ward linkage. A
+    /// weight of 2 merges like two copies of the item, apart from the
+    /// zero-height merge between the copies.
+    fn set_weights(&mut self, weights: Vec<f64>) {
+        assert_eq!(
+            self.distances.len(),
+            weights.len(),
+            "expected one weight per item, got {} weights for {} items",
+            weights.len(),
+            self.distances.len()
+        );
+        self.weights = weights;
+    }
+
+    /// Clusters sets of items, such as transactions, by Jaccard distance.
+    fn from_sets(sets: Vec<HashSet<char>>, method: LinkageMethod) -> Self {
+        let distances = sets
+            .iter()
+            .map(|a| sets.iter().map(|b| jaccard_distance(a, b)).collect())
+            .collect();
+        Self::from_distance_matrix(distances, method)
     }
 
     fn cluster_distance(&self, cluster_a: &Cluster, cluster_b: &Cluster) -> f64 {
@@ -71,6 +142,17 @@ impl HierarchicalClustering {
             LinkageMethod::Single => self.single_link_distance(cluster_a, cluster_b),
             LinkageMethod::Complete => self.complete_link_distance(cluster_a, cluster_b),
             LinkageMethod::Average => self.average_link_distance(cluster_a, cluster_b),
+            LinkageMethod::WeightedAverage => self.weighted_average_distance(cluster_a, cluster_b),
+            LinkageMethod::Centroid => {
+                let (centroid_a, _) = self.centroid(&cluster_a.points);
+                let (centroid_b, _) = self.centroid(&cluster_b.points);
+                centroid_a.distance(&centroid_b)
+            }
+            LinkageMethod::Ward => {
+                let (centroid_a, weight_a) = self.centroid(&cluster_a.points);
+                let (centroid_b, weight_b) = self.centroid(&cluster_b.points);
+                weight_a * weight_b / (weight_a + weight_b) * centroid_a.distance_sq(&centroid_b)
+            }
         }
     }
 
@@ -79,8 +161,8 @@ impl HierarchicalClustering {
         
         for &point_idx_a in &cluster_a.points {
             for &point_idx_b in &cluster_b.points {
-                let distance = self.data[point_idx_a].distance(&self.data[point_idx_b]);
-                if distance < min_distance {
+                let distance = self.distances[point_idx_a][point_idx_b];
+                if distance.total_cmp(&min_distance).is_lt() {
                     min_distance = distance;
                 }
             }
@@ -94,8 +176,8 @@ impl HierarchicalClustering {
         
         for &point_idx_a in &cluster_a.points {
             for &point_idx_b in &cluster_b.points {
-                let distance = self.data[point_idx_a].distance(&self.data[point_idx_b]);
-                if distance > max_distance {
+                let distance = self.distances[point_idx_a][point_idx_b];
+                if distance.total_cmp(&max_distance).is_gt() {
                     max_distance = distance;
                 }
             }
@@ -106,32 +188,61 @@ impl HierarchicalClustering {
 
     fn average_link_distance(&self, cluster_a: &Cluster, cluster_b: &Cluster) -> f64 {
         let mut sum_distance = 0.0;
-        let mut count = 0;
+        let mut total_weight = 0.0;
         
         for &point_idx_a in &cluster_a.points {
             for &point_idx_b in &cluster_b.points {
-                sum_distance += self.data[point_idx_a].distance(&self.data[point_idx_b]);
-                count += 1;
+                let weight = self.weights[point_idx_a] * self.weights[point_idx_b];
+                sum_distance += weight * self.distances[point_idx_a][point_idx_b];
+                total_weight += weight;
             }
         }
         
-        if count > 0 {
-            sum_distance / count as f64
+        if total_weight > 0.0 {
+            sum_distance / total_weight
         } else {
             f64::INFINITY
         }
     }
 
+    /// Lance-Williams WPGMA update `d(A ∪ B, C) = (d(A, C) + d(B, C)) / 2`,
+    /// unrolled over the merge history stored in the clusters.
+    fn weighted_average_distance(&self, cluster_a: &Cluster, cluster_b: &Cluster) -> f64 {
+        match (&cluster_a.left, &cluster_a.right, &cluster_b.left, &cluster_b.right) {
+            (Some(left), Some(right), _, _) => {
+                (self.weighted_average_distance(left, cluster_b)
+                    + self.weighted_average_distance(right, cluster_b))
+                    / 2.0
+            }
+            (_, _, Some(left), Some(right)) => {
+                (self.weighted_average_distance(cluster_a, left)
+                    + self.weighted_average_distance(cluster_a, right))
+                    / 2.0
+            }
+            _ => self.average_link_distance(cluster_a, cluster_b),
+        }
+    }
+
+    /// Returns the positions of the closest pair and their distance. Ties are
+    /// broken by cluster id rather than position: among pairs at the minimum
+    /// distance, the one with the smallest `(lower id, higher id)` wins, so the
+    /// dendrogram does not depend on how `fit` reorders the working list.
+    /// Distances are ordered by `total_cmp`, so a NaN distance is never the
+    /// closest.
     fn find_closest_clusters(&self, clusters: &[Cluster]) -> (usize, usize, f64) {
         let mut min_distance = f64::INFINITY;
         let mut closest_pair = (0, 1);
+        let mut closest_ids = (usize::MAX, usize::MAX);
         
         for i in 0..clusters.len() {
             for j in (i + 1)..clusters.len() {
                 let distance = self.cluster_distance(&clusters[i], &clusters[j]);
-                if distance < min_distance {
+                let (a, b) = (clusters[i].id, clusters[j].id);
+                let ids = (a.min(b), a.max(b));
+                if distance.total_cmp(&min_distance).then(ids.cmp(&closest_ids)).is_lt() {
                     min_distance = distance;
                     closest_pair = (i, j);
+                    closest_ids = ids;
                 }
             }
         }
@@ -139,18 +250,72 @@ impl HierarchicalClustering {
         (closest_pair.0, closest_pair.1, min_distance)
     }
 
+    /// The full dendrogram. Panics if there are no points.
     fn fit(&self) -> Cluster {
-        let mut clusters: Vec<Cluster> = self.data.iter().enumerate()
-            .map(|(i, _)| Cluster::new(i, vec![i]))
+        self.merge_until(1).pop().expect("hierarchical clustering needs at least one point")
+    }
+
+    /// Stops agglomerating once `k` clusters remain and returns them as a
+    /// forest, which is cheaper than building the full tree and cutting it.
+    fn fit_to_k(&self, k: usize) -> Vec<Cluster> {
+        let n = self.distances.len();
+        assert!(
+            (1..=n).contains(&k),
+            "k must be between 1 and the number of points ({}), got {}",
+            n,
+            k
+        );
+        self.merge_until(k)
+    }
+
+    /// Flat labelling with `k` clusters: `labels[i]` is the position of point
+    /// `i`'s cluster in the `fit_to_k` forest.
+    fn cut_at_k(&self, k: usize) -> Vec<usize> {
+        let mut labels = vec![0; self.distances.len()];
+        for (label, cluster) in self.fit_to_k(k).iter().enumerate() {
+            for &point in &cluster.points {
+                labels[point] = label;
+            }
+        }
+        labels
+    }
+
+    /// Picks the number of flat clusters: the `k` in `k_range` whose
+    /// `cut_at_k` labelling has the highest silhouette over the stored
+    /// distances, so set-based and non-Euclidean instances are scored under
+    /// their own metric. Returns that `k` with its score; ties go to the
+    /// smaller `k`. Values of `k` above the number of items are skipped, and
+    /// `None` means no `k` was left, e.g. when there are no items.
+    fn best_cut(&self, k_range: RangeInclusive<usize>) -> Option<(usize, f64)> {
+        let n = self.distances.len();
+        let mut best: Option<(usize, f64)> = None;
+
+        for k in k_range.filter(|&k| k >= 1 && k <= n) {
+            let score = silhouette_from_distances(&self.distances, &self.cut_at_k(k));
+            if best.is_none_or(|(_, best_score)| score.total_cmp(&best_score).is_gt()) {
+                best = Some((k, score));
+            }
+        }
+
+        best
+    }
+
+    /// Merges the closest clusters until `k` remain. Panics if a point has a
+    /// NaN or infinite coordinate.
+    fn merge_until(&self, k: usize) -> Vec<Cluster> {
+        assert_finite(self.data);
+        
+        let mut clusters: Vec<Cluster> = (0..self.distances.len())
+            .map(|i| Cluster::new(i, vec![i]))
             .collect();
         
-        let mut next_cluster_id = self.data.len();
+        let mut next_cluster_id = self.distances.len();
         
-        while clusters.len() > 1 {
+        while clusters.len() > k {
             let (i, j, distance) = self.find_closest_clusters(&clusters);
             
-            let cluster_i = clusters.remove(if i > j { i } else { i });
-            let cluster_j = clusters.remove(if i > j { j } else { j - 1 });
+            let cluster_i = clusters.remove(i);
+            let cluster_j = clusters.remove(j - 1);
             
             let merged_cluster = Cluster::merge(
                 next_cluster_id,
@@ -163,7 +328,73 @@ impl HierarchicalClustering {
             clusters.push(merged_cluster);
         }
         
-        clusters.pop().unwrap()
+        clusters
+    }
+
+    /// Weighted centroid of the given points and their total weight. Panics
+    /// if the instance has no points, e.g. one built by `from_sets`.
+    fn centroid(&self, points: &[usize]) -> (Point, f64) {
+        assert!(
+            !self.data.is_empty(),
+            "centroid and Ward linkage need points, not only a distance matrix"
+        );
+        let total_weight: f64 = points.iter().map(|&idx| self.weights[idx]).sum();
+        let centroid = points
+            .iter()
+            .fold(Point::zeros(self.data[points[0]].dim()), |sum, &idx| {
+                sum.add(&self.data[idx].scale(self.weights[idx]))
+            })
+            .scale(1.0 / total_weight);
+        (centroid, total_weight)
+    }
+
+    /// Weighted sum of squared distances from the given points to their
+    /// weighted centroid.
+    fn cluster_sse(&self, points: &[usize]) -> f64 {
+        let (centroid, _) = self.centroid(points);
+        points
+            .iter()
+            .map(|&idx| self.weights[idx] * self.data[idx].distance(&centroid).powi(2))
+            .sum()
+    }
+
+    fn collect_merges<'c>(node: &'c Cluster, merges: &mut Vec<&'c Cluster>) {
+        if let (Some(left), Some(right)) = (&node.left, &node.right) {
+            merges.push(node);
+            Self::collect_merges(left, merges);
+            Self::collect_merges(right, merges);
+        }
+    }
+
+    /// Total within-cluster SSE of the flat clustering at every level of the
+    /// dendrogram, as `(number of clusters, SSE)` from `n` singletons down to a
+    /// single cluster. Merges are replayed in the order `fit` performed them.
+    fn merge_sse_profile(&self) -> Vec<(usize, f64)> {
+        let n = self.data.len();
+        if n == 0 {
+            return Vec::new();
+        }
+
+        let root = self.fit();
+        let mut merges = Vec::new();
+        Self::collect_merges(&root, &mut merges);
+        merges.sort_by_key(|node| node.id);
+
+        let mut sse_by_id: HashMap<usize, f64> = HashMap::new();
+        let mut total = 0.0;
+        let mut profile = vec![(n, total)];
+
+        for (step, node) in merges.iter().enumerate() {
+            for child in [&node.left, &node.right].into_iter().flatten() {
+                total -= sse_by_id.get(&child.id).copied().unwrap_or(0.0);
+            }
+            let sse = self.cluster_sse(&node.points);
+            sse_by_id.insert(node.id, sse);
+            total += sse;
+            profile.push((n - step - 1, total));
+        }
+
+        profile
     }
 
     fn print_dendrogram(&self, node: &Cluster, depth: usize) {
@@ -172,7 +403,10 @@ impl HierarchicalClustering {
         
         if node.points.len() <= 3 {
             println!("{}Points: {:?}", indent, node.points.iter()
-                .map(|&idx| format!("({:.1},{:.1})", self.data[idx].x, self.data[idx].y))
+                .map(|&idx| match self.data.get(idx) {
+                    Some(point) => format!("{:.1?}", point.coords),
+                    None => format!("#{}", idx),
+                })
                 .collect::<Vec<_>>());
         } else {
             println!("{}Contains {} points", indent, node.points.len());
@@ -187,31 +421,424 @@ impl HierarchicalClustering {
     }
 }
 
-fn run_clustering(data: &[Point], method: LinkageMethod, method_name: &str) {
+/// Hierarchical clustering as a `Clusterer`: builds the dendrogram with
+/// `method` and cuts it into `k` flat clusters.
+struct HierarchicalCut {
+    k: usize,
+    method: LinkageMethod,
+}
+
+impl Clusterer for HierarchicalCut {
+    fn fit(&mut self, data: &[Point]) -> Vec<Option<usize>> {
+        HierarchicalClustering::new(data, self.method)
+            .cut_at_k(self.k)
+            .into_iter()
+            .map(Some)
+            .collect()
+    }
+}
+
+fn run_clustering(
+    clustering: &mut HierarchicalClustering,
+    method: LinkageMethod,
+    method_name: &str,
+    k: usize,
+) {
     println!("\n=== {} Linkage Hierarchical Clustering ===", method_name);
     
-    let clustering = HierarchicalClustering::new(data.to_vec(), method);
+    clustering.set_method(method);
+    let data = clustering.data;
     let dendrogram = clustering.fit();
     
     println!("\nDendrogram structure:");
     clustering.print_dendrogram(&dendrogram, 0);
+    
+    let k = data.len().min(k);
+    println!("\nStopping at {} clusters:", k);
+    for cluster in clustering.fit_to_k(k) {
+        println!("Cluster {} (height: {:.2}): {:?}", cluster.id, cluster.height, cluster.points);
+    }
+    let labels: Vec<Option<usize>> = clustering.cut_at_k(k).into_iter().map(Some).collect();
+    println!("Within-cluster SSE of the cut: {:.3}", cluster_sse(data, &labels));
+    
+    println!("\nWithin-cluster SSE by number of clusters:");
+    for (clusters, sse) in clustering.merge_sse_profile() {
+        println!("{} clusters: {:.3}", clusters, sse);
+    }
 }
 
-fn main() {
-    let data = vec![
+fn main() -> io::Result<()> {
+    let args = Args::from_env_or_exit(&[cli::INPUT, cli::K]);
+    let data = match args.value_or_exit::<String>(cli::INPUT) {
+        Some(path) => read_points(BufReader::new(File::open(path)?))?,
+        None => vec![
+            Point::new(1.0, 1.0),
+            Point::new(1.0, 8.0),
+            Point::new(2.0, 2.0),
+            Point::new(2.0, 5.0),
+            Point::new(3.0, 1.0),
+            Point::new(4.0, 3.0),
+            Point::new(5.0, 2.0),
+            Point::new(6.0, 1.0),
+            Point::new(6.0, 8.0),
+            Point::new(8.0, 6.0),
+        ],
+    };
+    if data.is_empty() {
+        println!("No points to cluster.");
+        return Ok(());
+    }
+    let k = args.value_in_or_exit::<usize>(cli::K, 1..=data.len()).unwrap_or(3.min(data.len()));
+    
+    let mut clustering = HierarchicalClustering::new(&data, LinkageMethod::Single);
+    run_clustering(&mut clustering, LinkageMethod::Single, "Single", k);
+    run_clustering(&mut clustering, LinkageMethod::Complete, "Complete", k);
+    run_clustering(&mut clustering, LinkageMethod::Average, "Average", k);
+    run_clustering(&mut clustering, LinkageMethod::Centroid, "Centroid", k);
+    run_clustering(&mut clustering, LinkageMethod::Ward, "Ward", k);
+    
+    clustering.set_method(LinkageMethod::Single);
+    let single_height = clustering.fit().height;
+    clustering.set_method(LinkageMethod::Complete);
+    let complete_height = clustering.fit().height;
+    println!(
+        "\nOne instance refit: final merge at {:.3} (single) and {:.3} (complete)",
+        single_height, complete_height
+    );
+    
+    clustering.set_method(LinkageMethod::Average);
+    if let Some((best_k, best_score)) = clustering.best_cut(1..=6) {
+        println!(
+            "\nBest average-linkage cut (k = 1..=6): k = {} (silhouette {:.4})",
+            best_k, best_score
+        );
+    }
+    
+    let square = vec![
+        Point::new(0.0, 0.0),
+        Point::new(1.0, 0.0),
+        Point::new(0.0, 1.0),
         Point::new(1.0, 1.0),
-        Point::new(1.0, 8.0),
-        Point::new(2.0, 2.0),
-        Point::new(2.0, 5.0),
-        Point::new(3.0, 1.0),
-        Point::new(4.0, 3.0),
-        Point::new(5.0, 2.0),
-        Point::new(6.0, 1.0),
-        Point::new(6.0, 8.0),
-        Point::new(8.0, 6.0),
     ];
+    run_clustering(
+        &mut HierarchicalClustering::new(&square, LinkageMethod::Single),
+        LinkageMethod::Single,
+        "Single (symmetric square)",
+        k,
+    );
     
-    run_clustering(&data, LinkageMethod::Single, "Single");
-    run_clustering(&data, LinkageMethod::Complete, "Complete");
-    run_clustering(&data, LinkageMethod::Average, "Average");
+    // With three points the final merge always joins a pair and a singleton,
+    // where UPGMA and WPGMA agree, so four points on a line are needed. Both
+    // merge {0, 1} at 1 and then 2.5 at 2; the last merge is at
+    // (10 + 9 + 7.5) / 3 = 8.833 for UPGMA and (9.5 + 7.5) / 2 = 8.5 for WPGMA.
+    let line = vec![
+        Point::new(0.0, 0.0),
+        Point::new(1.0, 0.0),
+        Point::new(2.5, 0.0),
+        Point::new(10.0, 0.0),
+    ];
+    for (method, name) in [(LinkageMethod::Average, "UPGMA"), (LinkageMethod::WeightedAverage, "WPGMA")] {
+        let root = HierarchicalClustering::new(&line, method).fit();
+        println!("\n{} final merge height on four collinear points: {:.3}", name, root.height);
+    }
+    
+    let mut weighted = HierarchicalClustering::new(&line, LinkageMethod::Ward);
+    weighted.set_weights(vec![1.0, 1.0, 2.0, 1.0]);
+    println!(
+        "Ward final merge height with point 2 counted twice: {:.3}",
+        weighted.fit().height
+    );
+    
+    let clustering = HierarchicalClustering::with_metric(&data, LinkageMethod::Single, &Minkowski::new(1.0));
+    let dendrogram = clustering.fit();
+    println!("\n=== Single Linkage Hierarchical Clustering with Minkowski p = 1 ===");
+    println!("\nDendrogram structure:");
+    clustering.print_dendrogram(&dendrogram, 0);
+    
+    println!("\n=== Complete Linkage Clustering with a Categorical Second Coordinate ===");
+    let mixed_data = vec![
+        Point::new(1.0, 0.0),
+        Point::new(1.5, 0.0),
+        Point::new(1.2, 1.0),
+        Point::new(8.0, 1.0),
+        Point::new(8.5, 1.0),
+    ];
+    let clustering = HierarchicalClustering::with_metric(
+        &mixed_data,
+        LinkageMethod::Complete,
+        &MixedDistance::new(vec![false, true]),
+    );
+    let dendrogram = clustering.fit();
+    println!("\nDendrogram structure:");
+    clustering.print_dendrogram(&dendrogram, 0);
+    
+    println!("\n=== Average Linkage Clustering of Proportions by Bray-Curtis Dissimilarity ===");
+    let (a, b) = (Point::new(1.0, 2.0), Point::new(3.0, 1.0));
+    println!(
+        "(1, 2) to (3, 1): Canberra {:.4} (2/4 + 1/3), Bray-Curtis {:.4} (3/7)",
+        canberra(&a, &b),
+        bray_curtis(&a, &b)
+    );
+    let proportions = vec![
+        Point::new(0.7, 0.3),
+        Point::new(0.65, 0.35),
+        Point::new(0.2, 0.8),
+        Point::new(0.25, 0.75),
+    ];
+    let clustering = HierarchicalClustering::with_metric(&proportions, LinkageMethod::Average, &Metric::BrayCurtis);
+    let dendrogram = clustering.fit();
+    println!("\nDendrogram structure:");
+    clustering.print_dendrogram(&dendrogram, 0);
+    
+    println!("\n=== Average Linkage Clustering of Transactions by Jaccard Distance ===");
+    let transactions: Vec<HashSet<char>> = [
+        "abcd", "bcd", "aefgh", "bcdegj", "bcdef", "afg", "aij", "abeh", "fghij", "efh",
+    ]
+    .iter()
+    .map(|items| items.chars().collect())
+    .collect();
+    let clustering = HierarchicalClustering::from_sets(transactions, LinkageMethod::Average);
+    let dendrogram = clustering.fit();
+    println!("\nDendrogram structure:");
+    clustering.print_dendrogram(&dendrogram, 0);
+    
+    println!("\n=== Scoring Cuts Through the Clusterer Trait ===");
+    let k = data.len().min(k);
+    let mut clusterers: Vec<(&str, Box<dyn Clusterer>)> = vec![
+        ("Single", Box::new(HierarchicalCut { k, method: LinkageMethod::Single })),
+        ("Complete", Box::new(HierarchicalCut { k, method: LinkageMethod::Complete })),
+        ("Average", Box::new(HierarchicalCut { k, method: LinkageMethod::Average })),
+    ];
+    for (name, clusterer) in clusterers.iter_mut() {
+        let (silhouette, db_index) = evaluate(clusterer.as_mut(), &data);
+        println!("{} linkage, k = {}: silhouette {:.4}, Davies-Bouldin {:.4}", name, k, silhouette, db_index);
+    }
+    
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> Vec<Point> {
+        vec![
+            Point::new(1.0, 1.0),
+            Point::new(1.0, 8.0),
+            Point::new(2.0, 2.0),
+            Point::new(2.0, 5.0),
+            Point::new(3.0, 1.0),
+            Point::new(4.0, 3.0),
+            Point::new(5.0, 2.0),
+            Point::new(6.0, 1.0),
+            Point::new(6.0, 8.0),
+            Point::new(8.0, 6.0),
+        ]
+    }
+
+    fn merge_heights(clustering: &HierarchicalClustering) -> Vec<f64> {
+        let root = clustering.fit();
+        let mut merges = Vec::new();
+        HierarchicalClustering::collect_merges(&root, &mut merges);
+        merges.sort_by_key(|node| node.id);
+        merges.iter().map(|node| node.height).collect()
+    }
+
+    #[test]
+    fn weight_two_merges_like_a_physical_copy() {
+        let line: Vec<Point> = vec![(0.0, 0.0).into(), (1.0, 0.0).into(), (2.5, 0.0).into(), (10.0, 0.0).into()];
+        let mut duplicated_line = line.clone();
+        duplicated_line.push(line[2].clone());
+
+        for method in [LinkageMethod::Average, LinkageMethod::Centroid, LinkageMethod::Ward] {
+            let mut weighted = HierarchicalClustering::new(&line, method);
+            weighted.set_weights(vec![1.0, 1.0, 2.0, 1.0]);
+            let duplicated = HierarchicalClustering::new(&duplicated_line, method);
+
+            // The copy first merges with point 2 at height 0; every later
+            // merge matches the weighted dendrogram.
+            let (weighted_heights, duplicated_heights) = (merge_heights(&weighted), merge_heights(&duplicated));
+            assert_eq!(duplicated_heights[0], 0.0, "{:?}", method);
+            assert_eq!(duplicated_heights[1..], weighted_heights[..], "{:?}", method);
+        }
+    }
+
+    #[test]
+    fn ward_and_centroid_heights_on_a_line() {
+        let line: Vec<Point> = vec![(0.0, 0.0).into(), (1.0, 0.0).into(), (2.5, 0.0).into(), (10.0, 0.0).into()];
+
+        // {0, 1} at 1, then 2.5 joins the centroid 0.5, then 10 joins 7/6.
+        let centroid = merge_heights(&HierarchicalClustering::new(&line, LinkageMethod::Centroid));
+        assert_eq!(centroid[..2], [1.0, 2.0]);
+        assert!((centroid[2] - (10.0 - 3.5 / 3.0)).abs() < 1e-12);
+
+        // Ward: 1 * 1 / 2 * 1², 2 * 1 / 3 * 2², 3 * 1 / 4 * (53 / 6)².
+        let ward = merge_heights(&HierarchicalClustering::new(&line, LinkageMethod::Ward));
+        let expected = [0.5, 8.0 / 3.0, 0.75 * (53.0f64 / 6.0).powi(2)];
+        for (height, expected) in ward.iter().zip(expected) {
+            assert!((height - expected).abs() < 1e-12, "{:?}", ward);
+        }
+    }
+
+    #[test]
+    fn unit_weights_change_nothing() {
+        let line: Vec<Point> = vec![(0.0, 0.0).into(), (1.0, 0.0).into(), (2.5, 0.0).into(), (10.0, 0.0).into()];
+        for method in [LinkageMethod::Average, LinkageMethod::Centroid, LinkageMethod::Ward] {
+            let mut weighted = HierarchicalClustering::new(&line, method);
+            weighted.set_weights(vec![1.0; 4]);
+            assert_eq!(
+                merge_heights(&weighted),
+                merge_heights(&HierarchicalClustering::new(&line, method)),
+                "{:?}",
+                method
+            );
+        }
+    }
+
+    #[test]
+    fn best_cut_splits_two_separated_groups() {
+        let groups: Vec<Point> = vec![
+            (0.0, 0.0).into(),
+            (0.5, 0.2).into(),
+            (0.1, 0.6).into(),
+            (10.0, 10.0).into(),
+            (10.4, 9.8).into(),
+            (9.9, 10.5).into(),
+        ];
+        let clustering = HierarchicalClustering::new(&groups, LinkageMethod::Average);
+
+        let (k, score) = clustering.best_cut(1..=5).unwrap();
+        assert_eq!(k, 2);
+        assert!(score > 0.9, "silhouette {}", score);
+    }
+
+    #[test]
+    fn best_cut_scores_sets_by_their_own_distances() {
+        let sets: Vec<HashSet<char>> = ["abc", "abcd", "abd", "xyz", "xyzw", "xzw"]
+            .iter()
+            .map(|items| items.chars().collect())
+            .collect();
+        let clustering = HierarchicalClustering::from_sets(sets, LinkageMethod::Average);
+
+        assert_eq!(clustering.best_cut(1..=5).map(|(k, _)| k), Some(2));
+    }
+
+    #[test]
+    fn best_cut_without_items_is_none() {
+        let clustering = HierarchicalClustering::from_distance_matrix(Vec::new(), LinkageMethod::Single);
+        assert_eq!(clustering.best_cut(1..=3), None);
+    }
+
+    #[test]
+    fn one_instance_refits_under_another_linkage_without_copying() {
+        let line: Vec<Point> = vec![(0.0, 0.0).into(), (1.0, 0.0).into(), (2.5, 0.0).into(), (10.0, 0.0).into()];
+        let mut clustering = HierarchicalClustering::new(&line, LinkageMethod::Single);
+
+        assert_eq!(merge_heights(&clustering), [1.0, 1.5, 7.5]);
+        clustering.set_method(LinkageMethod::Complete);
+        assert_eq!(merge_heights(&clustering), [1.0, 2.5, 10.0]);
+        assert!(std::ptr::eq(clustering.data, line.as_slice()));
+    }
+
+    #[test]
+    fn upgma_and_wpgma_heights_match_hand_calculations() {
+        // Three points always end with a pair and a singleton, where both
+        // agree, so four points on a line are needed.
+        let line: Vec<Point> = vec![(0.0, 0.0).into(), (1.0, 0.0).into(), (2.5, 0.0).into(), (10.0, 0.0).into()];
+
+        let upgma = merge_heights(&HierarchicalClustering::new(&line, LinkageMethod::Average));
+        let wpgma = merge_heights(&HierarchicalClustering::new(&line, LinkageMethod::WeightedAverage));
+        assert_eq!(upgma[..2], [1.0, 2.0]);
+        assert_eq!(wpgma[..2], [1.0, 2.0]);
+        assert!((upgma[2] - (10.0 + 9.0 + 7.5) / 3.0).abs() < 1e-12);
+        assert!((wpgma[2] - (9.5 + 7.5) / 2.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn ties_on_a_symmetric_square_give_the_same_tree_every_run() {
+        let square: Vec<Point> = vec![(0.0, 0.0).into(), (1.0, 0.0).into(), (0.0, 1.0).into(), (1.0, 1.0).into()];
+
+        let tree = format!("{:?}", HierarchicalClustering::new(&square, LinkageMethod::Single).fit());
+        for _ in 0..5 {
+            assert_eq!(format!("{:?}", HierarchicalClustering::new(&square, LinkageMethod::Single).fit()), tree);
+        }
+
+        // Every merge is at distance 1, so the smallest id pair wins each time.
+        let root = HierarchicalClustering::new(&square, LinkageMethod::Single).fit();
+        let (left, right) = (root.left.unwrap(), root.right.unwrap());
+        assert_eq!((left.id, left.points.clone()), (4, vec![0, 1]));
+        assert_eq!((right.id, right.points.clone()), (5, vec![2, 3]));
+    }
+
+    #[test]
+    fn sse_never_decreases_as_clusters_are_merged() {
+        let data = sample();
+        // One cluster: 51.6 around x = 3.8 plus 72.1 around y = 3.7.
+        let total_sse = 123.7;
+
+        for method in [LinkageMethod::Single, LinkageMethod::Complete, LinkageMethod::Average, LinkageMethod::Ward] {
+            let profile = HierarchicalClustering::new(&data, method).merge_sse_profile();
+
+            assert_eq!(profile.len(), data.len());
+            assert_eq!(profile[0], (data.len(), 0.0));
+            let (clusters, sse) = profile[data.len() - 1];
+            assert_eq!(clusters, 1);
+            assert!((sse - total_sse).abs() < 1e-9, "{:?}: {}", method, sse);
+            assert!(profile.windows(2).all(|w| w[1].0 == w[0].0 - 1 && w[1].1 >= w[0].1 - 1e-9), "{:?}", method);
+        }
+    }
+
+    #[test]
+    fn jaccard_is_zero_for_identical_and_one_for_disjoint_sets() {
+        let set = |items: &str| items.chars().collect::<HashSet<char>>();
+
+        assert_eq!(jaccard_distance(&set("abc"), &set("abc")), 0.0);
+        assert_eq!(jaccard_distance(&set("abc"), &set("xyz")), 1.0);
+        assert_eq!(jaccard_distance(&set("abc"), &set("bcd")), 0.5);
+        assert_eq!(jaccard_distance(&set(""), &set("")), 0.0);
+
+        let clustering = HierarchicalClustering::from_sets(vec![set("ab"), set("ab"), set("xy")], LinkageMethod::Single);
+        assert_eq!(merge_heights(&clustering), [0.0, 1.0]);
+    }
+
+    #[test]
+    fn fit_to_k_stops_at_the_requested_forest() {
+        let data = sample();
+        let clustering = HierarchicalClustering::new(&data, LinkageMethod::Average);
+
+        let singletons = clustering.fit_to_k(data.len());
+        assert_eq!(singletons.len(), data.len());
+        assert!(singletons.iter().enumerate().all(|(i, cluster)| cluster.points == [i] && cluster.left.is_none()));
+
+        let forest = clustering.fit_to_k(1);
+        assert_eq!(forest.len(), 1);
+        assert_eq!(format!("{:?}", forest[0]), format!("{:?}", clustering.fit()));
+        assert_eq!(clustering.fit_to_k(3).len(), 3);
+    }
+
+    #[test]
+    #[should_panic(expected = "k must be between 1 and the number of points")]
+    fn fit_to_k_rejects_zero() {
+        let data = sample();
+        HierarchicalClustering::new(&data, LinkageMethod::Single).fit_to_k(0);
+    }
+
+    #[test]
+    fn boxed_clusterers_label_every_point() {
+        let data = sample();
+        let methods = [LinkageMethod::Single, LinkageMethod::Complete, LinkageMethod::Ward];
+        let mut clusterers: Vec<Box<dyn Clusterer>> = methods
+            .into_iter()
+            .map(|method| Box::new(HierarchicalCut { k: 3, method }) as Box<dyn Clusterer>)
+            .collect();
+
+        for clusterer in clusterers.iter_mut() {
+            let labels = clusterer.fit(&data);
+            assert_eq!(labels.len(), data.len());
+            let mut clusters: Vec<usize> = labels.into_iter().map(|label| label.expect("a cut labels every point")).collect();
+            clusters.sort();
+            clusters.dedup();
+            assert_eq!(clusters, (0..3).collect::<Vec<_>>());
+        }
+    }
 }
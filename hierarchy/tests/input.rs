@@ -0,0 +1,26 @@
+use std::process::{Command, Output};
+
+/// Runs the binary on `rows` written as a points CSV to a temporary file.
+fn run_on(name: &str, rows: &[&str]) -> Output {
+    let path = std::env::temp_dir().join(format!("hierarchy_{}_{}.csv", name, std::process::id()));
+    std::fs::write(&path, rows.join("\n")).unwrap();
+    let output = Command::new(env!("CARGO_BIN_EXE_hierarchy")).arg("--input").arg(&path).output().unwrap();
+    std::fs::remove_file(&path).unwrap();
+    output
+}
+
+fn assert_runs(output: &Output) {
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+}
+
+#[test]
+fn one_dimensional_input_runs_every_demo() {
+    assert_runs(&run_on("line", &["1", "2", "3", "8", "9", "10"]));
+}
+
+#[test]
+fn three_dimensional_input_runs_every_demo() {
+    let output = run_on("cube", &["1,1,1", "1,2,1", "2,2,2", "8,9,8", "9,9,9", "9,8,9"]);
+    assert_runs(&output);
+    assert!(String::from_utf8_lossy(&output.stdout).contains("[1.0, 2.0, 1.0]"));
+}
@@ -0,0 +1,115 @@
+use common::Point;
+
+/// Per-point distance bounds for Elkan's k-means (Elkan, 2003). `upper[i]`
+/// bounds the distance from point `i` to its assigned centroid from above and
+/// `lower[i][c]` bounds its distance to centroid `c` from below, so most
+/// distance computations can be skipped once the centroids settle down.
+pub struct ElkanBounds {
+    upper: Vec<f64>,
+    lower: Vec<Vec<f64>>,
+}
+
+impl ElkanBounds {
+    /// Assigns every point to its nearest centroid, ties going to the lower
+    /// index, and records the exact distances as the initial bounds.
+    pub fn new(centroids: &[Point], data: &[Point]) -> (Self, Vec<usize>) {
+        let lower: Vec<Vec<f64>> = data
+            .iter()
+            .map(|point| centroids.iter().map(|centroid| point.distance(centroid)).collect())
+            .collect();
+
+        let mut labels = Vec::with_capacity(data.len());
+        let mut upper = Vec::with_capacity(data.len());
+        for distances in &lower {
+            let mut cluster = 0;
+            for (c, &distance) in distances.iter().enumerate() {
+                if distance.total_cmp(&distances[cluster]).is_lt() {
+                    cluster = c;
+                }
+            }
+            labels.push(cluster);
+            upper.push(distances[cluster]);
+        }
+
+        (ElkanBounds { upper, lower }, labels)
+    }
+
+    /// Updates `labels` after the centroids moved from `previous` to
+    /// `centroids`. A centroid is only measured when the bounds cannot rule it
+    /// out; comparisons are strict so ties resolve to the lower index exactly
+    /// as in a full nearest-centroid scan.
+    pub fn reassign(&mut self, previous: &[Point], centroids: &[Point], data: &[Point], labels: &mut [usize]) {
+        let k = centroids.len();
+        let shifts: Vec<f64> = previous.iter().zip(centroids).map(|(old, new)| old.distance(new)).collect();
+
+        let between: Vec<Vec<f64>> = centroids
+            .iter()
+            .map(|a| centroids.iter().map(|b| a.distance(b)).collect())
+            .collect();
+        let half_nearest: Vec<f64> = (0..k)
+            .map(|c| {
+                (0..k)
+                    .filter(|&other| other != c)
+                    .map(|other| between[c][other])
+                    .fold(f64::INFINITY, f64::min)
+                    / 2.0
+            })
+            .collect();
+
+        for (i, point) in data.iter().enumerate() {
+            for (bound, shift) in self.lower[i].iter_mut().zip(&shifts) {
+                *bound = (*bound - shift).max(0.0);
+            }
+            self.upper[i] += shifts[labels[i]];
+
+            if self.upper[i] < half_nearest[labels[i]] {
+                continue;
+            }
+
+            let mut tight = false;
+            for c in 0..k {
+                let assigned = labels[i];
+                if c == assigned
+                    || self.upper[i] < self.lower[i][c]
+                    || self.upper[i] < between[assigned][c] / 2.0
+                {
+                    continue;
+                }
+
+                if !tight {
+                    self.upper[i] = point.distance(&centroids[assigned]);
+                    self.lower[i][assigned] = self.upper[i];
+                    tight = true;
+                    if self.upper[i] < self.lower[i][c] || self.upper[i] < between[assigned][c] / 2.0 {
+                        continue;
+                    }
+                }
+
+                let distance = point.distance(&centroids[c]);
+                self.lower[i][c] = distance;
+                if distance.total_cmp(&self.upper[i]).then(c.cmp(&assigned)).is_lt() {
+                    labels[i] = c;
+                    self.upper[i] = distance;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ties_go_to_the_lower_centroid_index() {
+        let centroids = vec![Point::new(0.0, 0.0), Point::new(2.0, 0.0), Point::new(1.0, 1.0)];
+        let data = vec![Point::new(1.0, 0.0), Point::new(3.0, 0.0)];
+
+        let (mut bounds, mut labels) = ElkanBounds::new(&centroids, &data);
+        assert_eq!(labels, vec![0, 1]);
+
+        let moved = vec![Point::new(0.0, 0.0), Point::new(2.0, 0.0), Point::new(1.0, 2.0)];
+        bounds.reassign(&centroids, &moved, &data, &mut labels);
+        assert_eq!(labels, vec![0, 1]);
+    }
+}
@@ -1,43 +1,158 @@
+mod elkan;
+
 use std::f64;
-use std::collections::HashMap;
-use rand::Rng;
+use std::fmt;
+use std::fs::File;
+use std::io::BufReader;
+use std::ops::RangeInclusive;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use elkan::ElkanBounds;
 
-#[derive(Debug, Clone, Copy)]
-struct Point {
-    x: f64,
-    y: f64,
+use common::cli::{self, Args};
+use common::metrics::{adjusted_rand_index, davies_bouldin, purity, silhouette_score};
+use common::{
+    assert_finite, distance_matrix, evaluate, first_non_finite, group_by_label, read_points,
+    Clusterer, ConsoleReporter, Distance, Euclidean, Manhattan, MixedDistance, Point, Reporter,
+    Silent,
+};
+
+#[derive(Debug, Clone, PartialEq)]
+enum KMeansError {
+    EmptyData,
+    KTooLarge { k: usize, n: usize },
+    KIsZero,
+    CentroidCountMismatch { k: usize, found: usize },
+    CentroidDimensionMismatch { index: usize, expected: usize, found: usize },
+    NonFinitePoint { index: usize },
 }
 
-impl Point {
-    fn new(x: f64, y: f64) -> Self {
-        Point { x, y }
+impl fmt::Display for KMeansError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            KMeansError::EmptyData => write!(f, "cannot cluster an empty dataset"),
+            KMeansError::KTooLarge { k, n } => {
+                write!(f, "k = {} is larger than the number of points ({})", k, n)
+            }
+            KMeansError::KIsZero => write!(f, "k must be at least 1"),
+            KMeansError::CentroidCountMismatch { k, found } => {
+                write!(f, "expected {} initial centroids for k = {}, got {}", k, k, found)
+            }
+            KMeansError::CentroidDimensionMismatch { index, expected, found } => write!(
+                f,
+                "initial centroid {} has {} coordinates but the points have {}",
+                index, found, expected
+            ),
+            KMeansError::NonFinitePoint { index } => {
+                write!(f, "point {} has a NaN or infinite coordinate", index)
+            }
+        }
     }
+}
 
-    fn distance(&self, other: &Point) -> f64 {
-        let dx = self.x - other.x;
-        let dy = self.y - other.y;
-        (dx * dx + dy * dy).sqrt()
-    }
+impl std::error::Error for KMeansError {}
 
-    fn add(&self, other: &Point) -> Point {
-        Point {
-            x: self.x + other.x,
-            y: self.y + other.y,
-        }
+/// Outcome of `KMeans::fit`: the winning run's centroids and labels together
+/// with its inertia and iteration count.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+struct KMeansModel {
+    centroids: Vec<Point>,
+    labels: Vec<usize>,
+    inertia: f64,
+    iterations: usize,
+}
+
+impl KMeansModel {
+    /// Assigns each of `points` to its nearest centroid, exactly as
+    /// `KMeans::predict` would on the model that produced it. Panics if a
+    /// point has a NaN or infinite coordinate.
+    fn predict(&self, points: &[Point]) -> Vec<usize> {
+        assert_finite(points);
+        nearest_centroids(&self.centroids, points)
+    }
+    
+    /// Writes the model as JSON so it can be reused without retraining.
+    #[cfg(feature = "serde")]
+    fn save<P: AsRef<std::path::Path>>(&self, path: P) -> std::io::Result<()> {
+        let file = File::create(path)?;
+        serde_json::to_writer(std::io::BufWriter::new(file), self)?;
+        Ok(())
+    }
+    
+    /// Reads a model written by `save`.
+    #[cfg(feature = "serde")]
+    fn load<P: AsRef<std::path::Path>>(path: P) -> std::io::Result<KMeansModel> {
+        let file = File::open(path)?;
+        Ok(serde_json::from_reader(BufReader::new(file))?)
     }
+}
 
-    fn scale(&self, factor: f64) -> Point {
-        Point {
-            x: self.x * factor,
-            y: self.y * factor,
-        }
+/// Rejects data with a NaN or infinite coordinate, naming the first such
+/// point. Every fit in this file checks its input with it.
+fn check_finite(data: &[Point]) -> Result<(), KMeansError> {
+    match first_non_finite(data) {
+        Some(index) => Err(KMeansError::NonFinitePoint { index }),
+        None => Ok(()),
     }
 }
 
+/// Index of the nearest centroid for every point; ties go to the lower index.
+fn nearest_centroids(centroids: &[Point], data: &[Point]) -> Vec<usize> {
+    data.iter()
+        .map(|point| {
+            let mut min_dist = f64::MAX;
+            let mut cluster = 0;
+            
+            for (i, centroid) in centroids.iter().enumerate() {
+                let dist = point.distance(centroid);
+                if dist.total_cmp(&min_dist).is_lt() {
+                    min_dist = dist;
+                    cluster = i;
+                }
+            }
+            
+            cluster
+        })
+        .collect()
+}
+
+/// How `KMeans` picks its starting centroids.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+enum InitMethod {
+    /// `k` data points drawn at random (seeded by `KMeans::seed`).
+    #[default]
+    Random,
+    /// Farthest-first traversal: the point nearest the data mean, then
+    /// repeatedly the point farthest from all centroids chosen so far.
+    /// Deterministic, so it needs no RNG.
+    FarthestFirst,
+}
+
+/// How `KMeans` reassigns points after each centroid update. Both give
+/// identical labels; `Elkan` skips distance computations that the triangle
+/// inequality proves cannot change a point's cluster.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+enum Algorithm {
+    #[default]
+    Lloyd,
+    Elkan,
+}
+
 struct KMeans {
     k: usize,
     max_iterations: usize,
     centroids: Vec<Point>,
+    seed: Option<u64>,
+    n_init: usize,
+    tol: f64,
+    init: InitMethod,
+    algorithm: Algorithm,
+    reporter: Box<dyn Reporter>,
+    initial_centroids: Option<Vec<Point>>,
+    /// Points absorbed by each centroid, kept up to date by `partial_fit`.
+    counts: Vec<usize>,
 }
 
 impl KMeans {
@@ -46,42 +161,113 @@ impl KMeans {
             k,
             max_iterations,
             centroids: Vec::new(),
+            seed: None,
+            n_init: 1,
+            tol: 1e-6,
+            init: InitMethod::Random,
+            algorithm: Algorithm::Lloyd,
+            reporter: Box::new(Silent),
+            initial_centroids: None,
+            counts: Vec::new(),
+        }
+    }
+
+    /// Like `new`, but centroid initialization draws from a `StdRng` seeded
+    /// with `seed`, so the same seed and data always give the same clusters.
+    fn with_seed(k: usize, max_iterations: usize, seed: u64) -> Self {
+        KMeans {
+            seed: Some(seed),
+            ..KMeans::new(k, max_iterations)
+        }
+    }
+
+    /// Starts every run of `fit` from `centroids` instead of initializing
+    /// them, e.g. to warm-start from a previous model or pin known locations.
+    /// `fit` fails unless exactly `k` centroids were given. Every restart then
+    /// begins at the same place, so `n_init > 1` adds nothing.
+    fn set_initial_centroids(&mut self, centroids: Vec<Point>) {
+        self.initial_centroids = Some(centroids);
+    }
+    
+    /// RNG for the `run`-th restart. With a seed, every restart gets its own
+    /// derived seed so restarts differ but the whole fit stays reproducible.
+    fn rng(&self, run: usize) -> StdRng {
+        match self.seed {
+            Some(seed) => StdRng::seed_from_u64(seed.wrapping_add(run as u64)),
+            None => StdRng::from_rng(rand::thread_rng()).expect("thread_rng failed to seed StdRng"),
         }
     }
 
-    fn initialize_centroids(&mut self, data: &[Point]) {
-        let mut rng = rand::thread_rng();
-        let mut centroids = Vec::with_capacity(self.k);
+    fn initialize_centroids(&mut self, data: &[Point], run: usize) -> Result<(), KMeansError> {
+        if data.is_empty() {
+            return Err(KMeansError::EmptyData);
+        }
+        if self.k == 0 {
+            return Err(KMeansError::KIsZero);
+        }
+        if self.k > data.len() {
+            return Err(KMeansError::KTooLarge { k: self.k, n: data.len() });
+        }
         
-        for _ in 0..self.k {
-            let index = rng.gen_range(0..data.len());
-            centroids.push(data[index]);
+        self.centroids = match self.init {
+            InitMethod::Random => {
+                let mut rng = self.rng(run);
+                (0..self.k)
+                    .map(|_| data[rng.gen_range(0..data.len())].clone())
+                    .collect()
+            }
+            InitMethod::FarthestFirst => Self::farthest_first(data, self.k),
+        };
+        Ok(())
+    }
+    
+    /// Farthest-first traversal over `data`; ties go to the lowest index, so
+    /// the chosen centroids are always `k` distinct data points.
+    fn farthest_first(data: &[Point], k: usize) -> Vec<Point> {
+        let mean = data
+            .iter()
+            .skip(1)
+            .fold(data[0].clone(), |acc, point| acc.add(point))
+            .scale(1.0 / data.len() as f64);
+        
+        let mut first = 0;
+        for (i, point) in data.iter().enumerate() {
+            if point.distance(&mean).total_cmp(&data[first].distance(&mean)).is_lt() {
+                first = i;
+            }
         }
         
-        self.centroids = centroids;
+        let mut chosen = vec![first];
+        let mut min_dist: Vec<f64> = data.iter().map(|point| point.distance(&data[first])).collect();
+        
+        while chosen.len() < k {
+            let mut next = None;
+            for i in (0..data.len()).filter(|i| !chosen.contains(i)) {
+                if next.is_none_or(|best: usize| min_dist[i].total_cmp(&min_dist[best]).is_gt()) {
+                    next = Some(i);
+                }
+            }
+            
+            let next = next.expect("k <= n leaves an unchosen point");
+            chosen.push(next);
+            for (i, point) in data.iter().enumerate() {
+                min_dist[i] = min_dist[i].min(point.distance(&data[next]));
+            }
+        }
+        
+        chosen.into_iter().map(|i| data[i].clone()).collect()
     }
     
     fn assign_clusters(&self, data: &[Point]) -> Vec<usize> {
-        data.iter()
-            .map(|point| {
-                let mut min_dist = f64::MAX;
-                let mut cluster = 0;
-                
-                for (i, centroid) in self.centroids.iter().enumerate() {
-                    let dist = point.distance(centroid);
-                    if dist < min_dist {
-                        min_dist = dist;
-                        cluster = i;
-                    }
-                }
-                
-                cluster
-            })
-            .collect()
+        nearest_centroids(&self.centroids, data)
     }
     
+    /// Moves every centroid to the mean of its points. A centroid that lost
+    /// all of its points is reseeded to the data point farthest from its own
+    /// centroid, so no cluster stays empty for the rest of the fit. Returns
+    /// `false` once converged, i.e. when no centroid moved by more than `tol`.
     fn update_centroids(&mut self, data: &[Point], clusters: &[usize]) -> bool {
-        let mut new_centroids = vec![Point::new(0.0, 0.0); self.k];
+        let mut new_centroids = vec![Point::zeros(data[0].dim()); self.k];
         let mut counts = vec![0; self.k];
         
         for (point, &cluster) in data.iter().zip(clusters.iter()) {
@@ -89,46 +275,202 @@ impl KMeans {
             counts[cluster] += 1;
         }
         
-        for i in 0..self.k {
-            if counts[i] > 0 {
-                new_centroids[i] = new_centroids[i].scale(1.0 / counts[i] as f64);
-            } else {
-                new_centroids[i] = self.centroids[i];
+        for (new_centroid, &count) in new_centroids.iter_mut().zip(&counts) {
+            if count > 0 {
+                *new_centroid = new_centroid.scale(1.0 / count as f64);
             }
         }
         
-        let mut changed = false;
-        for i in 0..self.k {
-            if new_centroids[i].distance(&self.centroids[i]) > 1e-6 {
-                changed = true;
+        let mut stolen: Vec<usize> = Vec::new();
+        for empty in (0..self.k).filter(|&cluster| counts[cluster] == 0) {
+            let farthest = (0..data.len())
+                .filter(|i| !stolen.contains(i))
+                .map(|i| (i, data[i].distance(&new_centroids[clusters[i]])))
+                .max_by(|a, b| a.1.total_cmp(&b.1));
+            
+            match farthest {
+                Some((i, _)) => {
+                    new_centroids[empty] = data[i].clone();
+                    stolen.push(i);
+                }
+                None => new_centroids[empty] = self.centroids[empty].clone(),
             }
-            self.centroids[i] = new_centroids[i];
         }
         
-        changed
+        let mut max_shift: f64 = 0.0;
+        for (centroid, new_centroid) in self.centroids.iter_mut().zip(new_centroids) {
+            max_shift = max_shift.max(new_centroid.distance(centroid));
+            *centroid = new_centroid;
+        }
+        
+        max_shift > self.tol
     }
     
-    fn fit(&mut self, data: &[Point]) -> Vec<usize> {
-        self.initialize_centroids(data);
-        
-        let mut clusters = self.assign_clusters(data);
+    /// Iterates from the current centroids until they settle or
+    /// `max_iterations` is reached. When `trajectory` is given, the centroids
+    /// after every update are appended to it.
+    fn lloyd<F: FnMut(usize, f64)>(
+        &mut self,
+        data: &[Point],
+        on_iter: &mut F,
+        mut trajectory: Option<&mut Vec<Vec<Point>>>,
+    ) -> (Vec<usize>, usize) {
+        let (mut bounds, mut clusters) = match self.algorithm {
+            Algorithm::Lloyd => (None, self.assign_clusters(data)),
+            Algorithm::Elkan => {
+                let (bounds, clusters) = ElkanBounds::new(&self.centroids, data);
+                (Some(bounds), clusters)
+            }
+        };
         let mut iteration = 0;
         
         while iteration < self.max_iterations {
+            let previous = self.centroids.clone();
             let changed = self.update_centroids(data, &clusters);
+            if let Some(trajectory) = trajectory.as_mut() {
+                trajectory.push(self.centroids.clone());
+            }
             
             if !changed {
                 break;
             }
             
-            clusters = self.assign_clusters(data);
+            match bounds.as_mut() {
+                Some(bounds) => bounds.reassign(&previous, &self.centroids, data, &mut clusters),
+                None => clusters = self.assign_clusters(data),
+            }
             iteration += 1;
+            on_iter(iteration, self.inertia(data, &clusters));
         }
         
-        println!("Converged after {} iterations", iteration);
-        println!("Final centroids: {:?}", self.centroids);
+        (clusters, iteration)
+    }
+    
+    /// Runs Lloyd's algorithm `n_init` times from different initializations
+    /// and keeps the centroids and labels of the run with the lowest inertia.
+    fn fit(&mut self, data: &[Point]) -> Result<KMeansModel, KMeansError> {
+        self.fit_with_progress(data, |_, _| {})
+    }
+    
+    /// `fit` that calls `on_iter(iteration, inertia)` after every Lloyd
+    /// iteration. Iterations are counted from 1 and restart with each of the
+    /// `n_init` runs.
+    fn fit_with_progress<F: FnMut(usize, f64)>(
+        &mut self,
+        data: &[Point],
+        on_iter: F,
+    ) -> Result<KMeansModel, KMeansError> {
+        self.fit_runs(data, on_iter, false).map(|(model, _)| model)
+    }
+    
+    /// Fits a `KMeans::with_seed(k, max_iterations, seed)` and returns its
+    /// labels, centroids and inertia in one call.
+    fn fit_predict(
+        data: &[Point],
+        k: usize,
+        max_iterations: usize,
+        seed: u64,
+    ) -> Result<(Vec<usize>, Vec<Point>, f64), KMeansError> {
+        let model = KMeans::with_seed(k, max_iterations, seed).fit(data)?;
+        Ok((model.labels, model.centroids, model.inertia))
+    }
+    
+    /// `fit` that also returns the centroids at every iteration of the kept
+    /// run, starting with the initial ones. The last snapshot equals
+    /// `self.centroids`, so the whole convergence can be replayed.
+    fn fit_tracked(&mut self, data: &[Point]) -> Result<(Vec<usize>, Vec<Vec<Point>>), KMeansError> {
+        let (model, trajectory) = self.fit_runs(data, |_, _| {}, true)?;
+        Ok((model.labels, trajectory))
+    }
+    
+    /// The restarts behind `fit_with_progress` and `fit_tracked`. The returned
+    /// trajectory belongs to the best run and is empty unless `track` is set.
+    fn fit_runs<F: FnMut(usize, f64)>(
+        &mut self,
+        data: &[Point],
+        mut on_iter: F,
+        track: bool,
+    ) -> Result<(KMeansModel, Vec<Vec<Point>>), KMeansError> {
+        let mut best: Option<(KMeansModel, Vec<Vec<Point>>)> = None;
+        
+        check_finite(data)?;
         
-        clusters
+        if let Some(initial) = &self.initial_centroids {
+            if data.is_empty() {
+                return Err(KMeansError::EmptyData);
+            }
+            if self.k == 0 {
+                return Err(KMeansError::KIsZero);
+            }
+            if initial.len() != self.k {
+                return Err(KMeansError::CentroidCountMismatch { k: self.k, found: initial.len() });
+            }
+            let expected = data[0].dim();
+            if let Some(index) = initial.iter().position(|c| c.dim() != expected) {
+                let found = initial[index].dim();
+                return Err(KMeansError::CentroidDimensionMismatch { index, expected, found });
+            }
+        }
+        
+        for run in 0..self.n_init.max(1) {
+            match &self.initial_centroids {
+                Some(initial) => self.centroids = initial.clone(),
+                None => self.initialize_centroids(data, run)?,
+            }
+            
+            let mut trajectory = track.then(|| vec![self.centroids.clone()]);
+            let (labels, iterations) = self.lloyd(data, &mut on_iter, trajectory.as_mut());
+            let inertia = self.inertia(data, &labels);
+            
+            if best.as_ref().is_none_or(|(model, _)| inertia.total_cmp(&model.inertia).is_lt()) {
+                let model = KMeansModel {
+                    centroids: self.centroids.clone(),
+                    labels,
+                    inertia,
+                    iterations,
+                };
+                best = Some((model, trajectory.unwrap_or_default()));
+            }
+        }
+        
+        let (model, trajectory) = best.expect("at least one k-means run");
+        self.centroids = model.centroids.clone();
+        self.counts = vec![0; self.k];
+        for &label in &model.labels {
+            self.counts[label] += 1;
+        }
+        
+        self.reporter.on_converged(model.iterations, &self.centroids);
+        
+        Ok((model, trajectory))
+    }
+    
+    /// Sequential k-means: moves the centroid nearest to `point` toward it by
+    /// `1 / (count + 1)`, so each centroid stays the mean of every point it
+    /// has absorbed, including those from `fit`. Before any `fit`, the first
+    /// `k` points become the centroids. Panics if `point` has a NaN or
+    /// infinite coordinate.
+    fn partial_fit(&mut self, point: &Point) {
+        assert!(self.k > 0, "k-means needs k >= 1");
+        assert_finite(std::slice::from_ref(point));
+        
+        if self.centroids.len() < self.k {
+            self.centroids.push(point.clone());
+            self.counts.push(1);
+            return;
+        }
+        
+        let nearest = nearest_centroids(&self.centroids, std::slice::from_ref(point))[0];
+        self.counts[nearest] += 1;
+        let step = 1.0 / self.counts[nearest] as f64;
+        self.centroids[nearest] = self.centroids[nearest].scale(1.0 - step).add(&point.scale(step));
+    }
+    
+    /// Assigns each of `points` to its nearest fitted centroid. Panics if a
+    /// point has a NaN or infinite coordinate.
+    fn predict(&self, points: &[Point]) -> Vec<usize> {
+        assert_finite(points);
+        self.assign_clusters(points)
     }
     
     fn inertia(&self, data: &[Point], clusters: &[usize]) -> f64 {
@@ -139,38 +481,1286 @@ impl KMeans {
     }
 }
 
-fn main() {
-    let data = vec![
-        Point::new(1.0, 1.0),
-        Point::new(1.0, 8.0),
-        Point::new(2.0, 2.0),
-        Point::new(2.0, 5.0),
-        Point::new(3.0, 1.0),
-        Point::new(4.0, 3.0),
-        Point::new(5.0, 2.0),
-        Point::new(6.0, 1.0),
-        Point::new(6.0, 8.0),
-        Point::new(8.0, 6.0),
-    ];
+/// Panics if `fit` rejects the data, e.g. when `k` exceeds the number of
+/// points.
+impl Clusterer for KMeans {
+    fn fit(&mut self, data: &[Point]) -> Vec<Option<usize>> {
+        match KMeans::fit(self, data) {
+            Ok(model) => model.labels.into_iter().map(Some).collect(),
+            Err(err) => panic!("k-means failed: {}", err),
+        }
+    }
+}
+
+/// Divisive k-means: starts from a single cluster and repeatedly splits the
+/// cluster with the highest SSE using a 2-means run until `k` clusters exist.
+struct BisectingKMeans {
+    k: usize,
+    max_iterations: usize,
+    seed: Option<u64>,
+}
+
+impl BisectingKMeans {
+    fn new(k: usize, max_iterations: usize) -> Self {
+        BisectingKMeans {
+            k,
+            max_iterations,
+            seed: None,
+        }
+    }
     
-    for k in 2..=5 {
-        println!("\nRunning k-means with k = {}", k);
-        let mut kmeans = KMeans::new(k, 100);
-        let clusters = kmeans.fit(&data);
+    /// Like `new`, but the split producing cluster `label` runs a 2-means
+    /// seeded with `seed + label`, so the same seed always gives the same
+    /// clusters.
+    fn with_seed(k: usize, max_iterations: usize, seed: u64) -> Self {
+        BisectingKMeans {
+            seed: Some(seed),
+            ..BisectingKMeans::new(k, max_iterations)
+        }
+    }
+    
+    /// Sum of squared distances from `points` to their mean.
+    fn sse(points: &[Point]) -> f64 {
+        let sum = points
+            .iter()
+            .skip(1)
+            .fold(points[0].clone(), |acc, point| acc.add(point));
+        let mean = sum.scale(1.0 / points.len() as f64);
         
-        let inertia = kmeans.inertia(&data, &clusters);
-        println!("Inertia (sum of squared distances): {:.4}", inertia);
+        points.iter().map(|point| point.distance_sq(&mean)).sum()
+    }
+    
+    fn fit(&self, data: &[Point]) -> Result<Vec<usize>, KMeansError> {
+        check_finite(data)?;
+        if data.is_empty() {
+            return Err(KMeansError::EmptyData);
+        }
+        if self.k == 0 {
+            return Err(KMeansError::KIsZero);
+        }
+        if self.k > data.len() {
+            return Err(KMeansError::KTooLarge { k: self.k, n: data.len() });
+        }
         
-        println!("Cluster assignments:");
-        let mut cluster_map: HashMap<usize, Vec<(f64, f64)>> = HashMap::new();
+        let mut labels = vec![0; data.len()];
         
-        for (i, point) in data.iter().enumerate() {
-            let cluster = clusters[i];
-            cluster_map.entry(cluster).or_insert_with(Vec::new).push((point.x, point.y));
+        for next_label in 1..self.k {
+            let mut target = None;
+            let mut target_sse = f64::NEG_INFINITY;
+            
+            for label in 0..next_label {
+                let members: Vec<Point> = data
+                    .iter()
+                    .zip(&labels)
+                    .filter(|(_, &l)| l == label)
+                    .map(|(point, _)| point.clone())
+                    .collect();
+                
+                if members.len() < 2 {
+                    continue;
+                }
+                
+                let sse = Self::sse(&members);
+                if sse.total_cmp(&target_sse).is_gt() {
+                    target_sse = sse;
+                    target = Some(label);
+                }
+            }
+            
+            let target = target.expect("k <= n guarantees a splittable cluster");
+            let indices: Vec<usize> = (0..data.len()).filter(|&i| labels[i] == target).collect();
+            let members: Vec<Point> = indices.iter().map(|&i| data[i].clone()).collect();
+            
+            let mut kmeans = KMeans::new(2, self.max_iterations);
+            kmeans.seed = self.seed.map(|seed| seed.wrapping_add(next_label as u64));
+            let split = kmeans.fit(&members)?.labels;
+            
+            // Identical points can leave one side of the split empty; peel one
+            // point off so every split produces two non-empty clusters.
+            let moved_any = split.contains(&1) && split.contains(&0);
+            for (pos, &i) in indices.iter().enumerate() {
+                if split[pos] == 1 || (!moved_any && pos == 0) {
+                    labels[i] = next_label;
+                }
+            }
+        }
+        
+        Ok(labels)
+    }
+}
+
+/// Mini-batch k-means (Sculley, 2010): each iteration samples `batch_size`
+/// points and moves their nearest centroids towards them with a per-centroid
+/// learning rate of `1 / count`, avoiding a full pass over the data.
+struct MiniBatchKMeans {
+    k: usize,
+    max_iterations: usize,
+    batch_size: usize,
+    seed: Option<u64>,
+}
+
+impl MiniBatchKMeans {
+    fn new(k: usize, max_iterations: usize, batch_size: usize) -> Self {
+        MiniBatchKMeans {
+            k,
+            max_iterations,
+            batch_size,
+            seed: None,
+        }
+    }
+    
+    fn with_seed(k: usize, max_iterations: usize, batch_size: usize, seed: u64) -> Self {
+        MiniBatchKMeans {
+            seed: Some(seed),
+            ..MiniBatchKMeans::new(k, max_iterations, batch_size)
+        }
+    }
+    
+    fn fit(&self, data: &[Point]) -> Result<KMeansModel, KMeansError> {
+        check_finite(data)?;
+        let mut kmeans = KMeans::new(self.k, self.max_iterations);
+        kmeans.seed = self.seed;
+        kmeans.initialize_centroids(data, 0)?;
+        
+        let mut rng = kmeans.rng(1);
+        let mut counts = vec![0usize; self.k];
+        
+        for _ in 0..self.max_iterations {
+            let batch: Vec<usize> = (0..self.batch_size).map(|_| rng.gen_range(0..data.len())).collect();
+            let nearest = kmeans.assign_clusters(&batch.iter().map(|&i| data[i].clone()).collect::<Vec<_>>());
+            
+            for (&i, &cluster) in batch.iter().zip(&nearest) {
+                counts[cluster] += 1;
+                let rate = 1.0 / counts[cluster] as f64;
+                kmeans.centroids[cluster] = kmeans.centroids[cluster]
+                    .scale(1.0 - rate)
+                    .add(&data[i].scale(rate));
+            }
+        }
+        
+        let labels = kmeans.assign_clusters(data);
+        let inertia = kmeans.inertia(data, &labels);
+        
+        Ok(KMeansModel {
+            centroids: kmeans.centroids,
+            labels,
+            inertia,
+            iterations: self.max_iterations,
+        })
+    }
+}
+
+/// Fuzzy c-means (Bezdek): every point belongs to every cluster with a
+/// membership in `[0, 1]`, and memberships of a point sum to 1. `m > 1`
+/// controls fuzziness; as `m` grows memberships approach `1 / k`.
+struct FuzzyCMeans {
+    k: usize,
+    max_iterations: usize,
+    m: f64,
+    seed: Option<u64>,
+}
+
+impl FuzzyCMeans {
+    fn new(k: usize, max_iterations: usize, m: f64) -> Self {
+        assert!(m > 1.0, "fuzziness exponent m must be greater than 1, got {}", m);
+        FuzzyCMeans {
+            k,
+            max_iterations,
+            m,
+            seed: None,
+        }
+    }
+    
+    fn with_seed(k: usize, max_iterations: usize, m: f64, seed: u64) -> Self {
+        FuzzyCMeans {
+            seed: Some(seed),
+            ..FuzzyCMeans::new(k, max_iterations, m)
+        }
+    }
+    
+    /// `u_ik = 1 / Σ_j (d_ik / d_jk)^(2/(m-1))`. A point sitting exactly on a
+    /// centroid belongs fully to that cluster.
+    fn memberships(&self, data: &[Point], centroids: &[Point]) -> Vec<Vec<f64>> {
+        let exponent = 2.0 / (self.m - 1.0);
+        data.iter()
+            .map(|point| {
+                let distances: Vec<f64> = centroids.iter().map(|c| point.distance(c)).collect();
+                if let Some(hit) = distances.iter().position(|&d| d == 0.0) {
+                    return (0..centroids.len()).map(|j| if j == hit { 1.0 } else { 0.0 }).collect();
+                }
+                distances
+                    .iter()
+                    .map(|&d_i| 1.0 / distances.iter().map(|&d_j| (d_i / d_j).powf(exponent)).sum::<f64>())
+                    .collect()
+            })
+            .collect()
+    }
+    
+    /// Returns the membership matrix (one row per point) and the fuzzy
+    /// centroids, each the mean of all points weighted by `u^m`.
+    fn fit(&self, data: &[Point]) -> Result<(Vec<Vec<f64>>, Vec<Point>), KMeansError> {
+        check_finite(data)?;
+        let mut kmeans = KMeans::new(self.k, self.max_iterations);
+        kmeans.seed = self.seed;
+        kmeans.initialize_centroids(data, 0)?;
+        let mut centroids = kmeans.centroids;
+        
+        for _ in 0..self.max_iterations {
+            let memberships = self.memberships(data, &centroids);
+            
+            let updated: Vec<Point> = (0..self.k)
+                .map(|j| {
+                    let weights: Vec<f64> = memberships.iter().map(|row| row[j].powf(self.m)).collect();
+                    let total: f64 = weights.iter().sum();
+                    data.iter()
+                        .zip(&weights)
+                        .fold(Point::zeros(data[0].dim()), |sum, (point, &w)| sum.add(&point.scale(w)))
+                        .scale(1.0 / total)
+                })
+                .collect();
+            
+            let max_shift = centroids
+                .iter()
+                .zip(&updated)
+                .map(|(old, new)| old.distance(new))
+                .fold(0.0, f64::max);
+            centroids = updated;
+            
+            if max_shift <= kmeans.tol {
+                break;
+            }
+        }
+        
+        Ok((self.memberships(data, &centroids), centroids))
+    }
+}
+
+/// Partitioning Around Medoids: like k-means, but every cluster center is an
+/// actual data point, so any `Distance` can be used.
+struct KMedoids<D: Distance> {
+    k: usize,
+    max_iterations: usize,
+    metric: D,
+}
+
+impl<D: Distance> KMedoids<D> {
+    fn new(k: usize, max_iterations: usize, metric: D) -> Self {
+        KMedoids { k, max_iterations, metric }
+    }
+    
+    fn cost(distances: &[Vec<f64>], medoids: &[usize]) -> f64 {
+        distances
+            .iter()
+            .map(|row| medoids.iter().map(|&m| row[m]).fold(f64::INFINITY, f64::min))
+            .sum()
+    }
+    
+    /// Returns the cluster label of every point and the indices of the
+    /// points chosen as medoids. Medoids are picked greedily (PAM BUILD) and
+    /// then improved by the best cost-reducing medoid swap each iteration.
+    fn fit(&self, data: &[Point]) -> Result<(Vec<usize>, Vec<usize>), KMeansError> {
+        check_finite(data)?;
+        if data.is_empty() {
+            return Err(KMeansError::EmptyData);
+        }
+        if self.k == 0 {
+            return Err(KMeansError::KIsZero);
+        }
+        if self.k > data.len() {
+            return Err(KMeansError::KTooLarge { k: self.k, n: data.len() });
+        }
+        
+        let n = data.len();
+        let distances = distance_matrix(data, &self.metric);
+        
+        let mut medoids: Vec<usize> = Vec::with_capacity(self.k);
+        while medoids.len() < self.k {
+            let best = (0..n)
+                .filter(|i| !medoids.contains(i))
+                .map(|candidate| {
+                    let mut trial = medoids.clone();
+                    trial.push(candidate);
+                    (candidate, Self::cost(&distances, &trial))
+                })
+                .min_by(|a, b| a.1.total_cmp(&b.1))
+                .map(|(candidate, _)| candidate)
+                .unwrap();
+            medoids.push(best);
+        }
+        
+        let mut cost = Self::cost(&distances, &medoids);
+        for _ in 0..self.max_iterations {
+            let mut best_swap = None;
+            let mut best_cost = cost;
+            
+            for slot in 0..self.k {
+                for candidate in (0..n).filter(|i| !medoids.contains(i)) {
+                    let mut trial = medoids.clone();
+                    trial[slot] = candidate;
+                    let trial_cost = Self::cost(&distances, &trial);
+                    if trial_cost.total_cmp(&best_cost).is_lt() {
+                        best_cost = trial_cost;
+                        best_swap = Some((slot, candidate));
+                    }
+                }
+            }
+            
+            let Some((slot, candidate)) = best_swap else {
+                break;
+            };
+            medoids[slot] = candidate;
+            cost = best_cost;
+        }
+        
+        let labels = distances
+            .iter()
+            .map(|row| {
+                (0..self.k)
+                    .min_by(|&a, &b| row[medoids[a]].total_cmp(&row[medoids[b]]))
+                    .unwrap()
+            })
+            .collect();
+        
+        Ok((labels, medoids))
+    }
+}
+
+/// Best-of-10-restarts inertia for every `k` in `k_range`, for plotting an
+/// elbow curve. Values of `k` that cannot be fitted on `data` are skipped.
+fn elbow(data: &[Point], k_range: RangeInclusive<usize>, max_iterations: usize) -> Vec<(usize, f64)> {
+    k_range
+        .filter_map(|k| {
+            let mut kmeans = KMeans::new(k, max_iterations);
+            kmeans.n_init = 10;
+            kmeans.fit(data).ok().map(|model| (k, model.inertia))
+        })
+        .collect()
+}
+
+/// Co-assignment stability: `n_runs` seeded k-means runs, each from the
+/// restart-specific initialization `fit` would use, and `[i][j]` is the
+/// fraction of runs in which points `i` and `j` share a cluster. Values near 1
+/// within a cluster and near 0 across clusters indicate robust structure.
+fn consensus(data: &[Point], k: usize, n_runs: usize, seed: u64) -> Result<Vec<Vec<f64>>, KMeansError> {
+    assert!(n_runs > 0, "consensus needs at least one run");
+    
+    let mut together = vec![vec![0usize; data.len()]; data.len()];
+    let mut kmeans = KMeans::with_seed(k, 100, seed);
+    
+    for run in 0..n_runs {
+        kmeans.initialize_centroids(data, run)?;
+        let (labels, _) = kmeans.lloyd(data, &mut |_, _| {}, None);
+        
+        for (i, row) in together.iter_mut().enumerate() {
+            for (j, count) in row.iter_mut().enumerate() {
+                if labels[i] == labels[j] {
+                    *count += 1;
+                }
+            }
         }
+    }
+    
+    Ok(together
+        .into_iter()
+        .map(|row| row.into_iter().map(|count| count as f64 / n_runs as f64).collect())
+        .collect())
+}
+
+/// Gap statistic (Tibshirani et al.) for every `k` in `k_range`, as
+/// `(k, gap, standard error)`. `n_refs` reference datasets are drawn uniformly
+/// from the bounding box of `data`; the gap is `mean(log W_ref) - log W_data`
+/// with `W` the best-of-10-restarts inertia, and the standard error is the
+/// spread of `log W_ref` scaled by `sqrt(1 + 1 / n_refs)`. Fits with zero
+/// inertia have no logarithm and are left out, so values of `k` that cannot
+/// be fitted on `data`, that fit `data` exactly, or that leave no usable
+/// reference are skipped.
+fn gap_statistic(
+    data: &[Point],
+    k_range: RangeInclusive<usize>,
+    n_refs: usize,
+    seed: u64,
+) -> Vec<(usize, f64, f64)> {
+    let Some(first) = data.first() else {
+        return Vec::new();
+    };
+    let (lower, upper) = data.iter().fold(
+        (first.coords.clone(), first.coords.clone()),
+        |(mut lower, mut upper), point| {
+            for (d, &c) in point.coords.iter().enumerate() {
+                lower[d] = lower[d].min(c);
+                upper[d] = upper[d].max(c);
+            }
+            (lower, upper)
+        },
+    );
+    
+    let mut rng = StdRng::seed_from_u64(seed);
+    let references: Vec<Vec<Point>> = (0..n_refs)
+        .map(|_| {
+            (0..data.len())
+                .map(|_| {
+                    Point::from_coords(
+                        lower.iter().zip(&upper).map(|(&lo, &hi)| rng.gen_range(lo..=hi)).collect(),
+                    )
+                })
+                .collect()
+        })
+        .collect();
+    
+    let log_inertia = |points: &[Point], k: usize| -> Option<f64> {
+        let mut kmeans = KMeans::with_seed(k, 100, seed);
+        kmeans.n_init = 10;
+        kmeans.fit(points).ok().filter(|model| model.inertia > 0.0).map(|model| model.inertia.ln())
+    };
+    
+    k_range
+        .filter_map(|k| {
+            let log_w = log_inertia(data, k)?;
+            let log_refs: Vec<f64> = references.iter().filter_map(|reference| log_inertia(reference, k)).collect();
+            if log_refs.is_empty() {
+                return None;
+            }
+            let count = log_refs.len() as f64;
+            let mean = log_refs.iter().sum::<f64>() / count;
+            let sd = (log_refs.iter().map(|l| (l - mean).powi(2)).sum::<f64>() / count).sqrt();
+            Some((k, mean - log_w, sd * (1.0 + 1.0 / count).sqrt()))
+        })
+        .collect()
+}
+
+/// Picks the knee of an elbow curve: after scaling both axes to `[0, 1]`, the
+/// `k` whose point lies farthest below the line joining the two endpoints.
+fn suggest_k(elbow: &[(usize, f64)]) -> usize {
+    let (Some(&(first_k, first_inertia)), Some(&(last_k, last_inertia))) = (elbow.first(), elbow.last()) else {
+        return 0;
+    };
+    
+    let k_span = (last_k - first_k).max(1) as f64;
+    let inertia_span = (first_inertia - last_inertia).abs().max(f64::EPSILON);
+    
+    elbow
+        .iter()
+        .map(|&(k, inertia)| {
+            let x = (k - first_k) as f64 / k_span;
+            let y = (inertia - last_inertia) / inertia_span;
+            (k, (1.0 - x) - y)
+        })
+        .max_by(|a, b| a.1.total_cmp(&b.1))
+        .map(|(k, _)| k)
+        .unwrap_or(first_k)
+}
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let args = Args::from_env_or_exit(&[cli::INPUT, cli::K]);
+    let data = match args.value_or_exit::<String>(cli::INPUT) {
+        Some(path) => read_points(BufReader::new(File::open(path)?))?,
+        None => vec![
+            Point::new(1.0, 1.0),
+            Point::new(1.0, 8.0),
+            Point::new(2.0, 2.0),
+            Point::new(2.0, 5.0),
+            Point::new(3.0, 1.0),
+            Point::new(4.0, 3.0),
+            Point::new(5.0, 2.0),
+            Point::new(6.0, 1.0),
+            Point::new(6.0, 8.0),
+            Point::new(8.0, 6.0),
+        ],
+    };
+    
+    let k_values = match args.value_or_exit::<usize>(cli::K) {
+        Some(k) => k..=k,
+        None => 2..=5,
+    };
+    for k in k_values {
+        println!("\nRunning k-means with k = {}", k);
+        let mut kmeans = KMeans::with_seed(k, 100, 42);
+        kmeans.n_init = 10;
+        kmeans.reporter = Box::new(ConsoleReporter);
+        let model = kmeans.fit(&data)?;
         
-        for (cluster, points) in cluster_map.iter() {
+        println!("Inertia (sum of squared distances): {:.4}", model.inertia);
+        println!("Silhouette score: {:.4}", silhouette_score(&data, &model.labels));
+        println!("Davies-Bouldin index: {:.4}", davies_bouldin(&data, &model.labels));
+        
+        println!("Cluster assignments:");
+        let groups = group_by_label(&data, &model.labels);
+        let mut cluster_ids: Vec<&usize> = groups.keys().collect();
+        cluster_ids.sort();
+        
+        for cluster in cluster_ids {
+            let points: Vec<&Vec<f64>> = groups[cluster].iter().map(|&i| &data[i].coords).collect();
             println!("Cluster {}: {:?}", cluster, points);
         }
     }
+    
+    let k = 3.min(data.len());
+    println!("\nRunning k-means with k = {} and farthest-first initialization", k);
+    let mut kmeans = KMeans::new(k, 100);
+    kmeans.init = InitMethod::FarthestFirst;
+    kmeans.reporter = Box::new(ConsoleReporter);
+    let model = kmeans.fit(&data)?;
+    println!("Centroids: {:?}", model.centroids.iter().map(|c| c.coords.clone()).collect::<Vec<_>>());
+    
+    let k = 3.min(data.len());
+    println!("\nRunning k-means with k = {} and Elkan's algorithm", k);
+    let mut kmeans = KMeans::with_seed(k, 100, 42);
+    kmeans.algorithm = Algorithm::Elkan;
+    kmeans.reporter = Box::new(ConsoleReporter);
+    println!("Cluster assignments: {:?}", kmeans.fit(&data)?.labels);
+    
+    let k = 3.min(data.len());
+    let mut kmeans = KMeans::with_seed(k, 100, 42);
+    let (_, trajectory) = kmeans.fit_tracked(&data)?;
+    println!("\nCentroid trajectory for k = {} ({} snapshots):", k, trajectory.len());
+    for (i, centroids) in trajectory.iter().enumerate() {
+        let coords: Vec<&Vec<f64>> = centroids.iter().map(|centroid| &centroid.coords).collect();
+        println!("{}: {:.3?}", i, coords);
+    }
+    
+    let k = 2.min(data.len());
+    let (labels, _, inertia) = KMeans::fit_predict(&data, k, 100, 42)?;
+    println!("\nfit_predict with k = {}: inertia {:.4}, labels {:?}", k, inertia, labels);
+    let mut kmeans = KMeans::with_seed(k, 100, 42);
+    kmeans.fit(&data)?;
+    let anchor = &data[data.len() - 1];
+    let burst: Vec<Point> = [0.0, 0.2, -0.2, 0.1]
+        .iter()
+        .map(|&offset| anchor.add(&Point::from_coords(vec![offset; anchor.dim()])))
+        .collect();
+    let target = nearest_centroids(&kmeans.centroids, &burst[..1])[0];
+    let before = kmeans.centroids[target].distance(&burst[0]);
+    for point in &burst {
+        kmeans.partial_fit(point);
+    }
+    println!(
+        "\nStreaming {} points near {:?} moves centroid {} from {:.3} to {:.3} away",
+        burst.len(),
+        anchor.coords,
+        target,
+        before,
+        kmeans.centroids[target].distance(&burst[0])
+    );
+    
+    println!("\nElbow curve for k = 1..=6");
+    let curve = elbow(&data, 1..=6, 100);
+    for (k, inertia) in &curve {
+        println!("k = {}: inertia {:.4}", k, inertia);
+    }
+    println!("Suggested k: {}", suggest_k(&curve));
+    
+    println!("\nGap statistic on two blobs for k = 1..=4");
+    let blobs: Vec<Point> = vec![
+        (0.0, 0.0).into(),
+        (0.5, 0.3).into(),
+        (0.2, 0.8).into(),
+        (0.7, 0.6).into(),
+        (10.0, 10.0).into(),
+        (10.4, 9.6).into(),
+        (9.7, 10.5).into(),
+        (10.2, 10.3).into(),
+    ];
+    for (k, gap, std_error) in gap_statistic(&blobs, 1..=4, 10, 42) {
+        println!("k = {}: gap {:.4} (standard error {:.4})", k, gap, std_error);
+    }
+    
+    println!("\nCo-assignment consensus over 20 runs with k = 2 on the two blobs");
+    let matrix = consensus(&blobs, 2, 20, 7)?;
+    let (mut within, mut across) = (f64::INFINITY, 0.0f64);
+    for (i, row) in matrix.iter().enumerate() {
+        for (j, &value) in row.iter().enumerate() {
+            if (i < 4) == (j < 4) {
+                within = within.min(value);
+            } else {
+                across = across.max(value);
+            }
+        }
+    }
+    println!("Lowest within-blob consensus: {:.2}, highest across blobs: {:.2}", within, across);
+    
+    println!("\nWarm-starting k-means at the true blob centers");
+    let mut kmeans = KMeans::new(2, 100);
+    kmeans.set_initial_centroids(vec![Point::new(0.35, 0.425), Point::new(10.075, 10.1)]);
+    let model = kmeans.fit(&blobs)?;
+    println!("Converged after {} iterations with inertia {:.4}", model.iterations, model.inertia);
+    kmeans.set_initial_centroids(vec![Point::new(0.0, 0.0)]);
+    if let Err(err) = kmeans.fit(&blobs) {
+        println!("Rejected a single pinned centroid: {}", err);
+    }
+    
+    println!("\nScoring k-means with k = 2 against the true blob labels");
+    let truth = [0, 0, 0, 0, 1, 1, 1, 1];
+    let mut kmeans = KMeans::with_seed(2, 100, 42);
+    kmeans.reporter = Box::new(ConsoleReporter);
+    let model = kmeans.fit(&blobs)?;
+    println!("Purity: {:.4}", purity(&model.labels, &truth));
+    println!("Adjusted Rand index: {:.4}", adjusted_rand_index(&model.labels, &truth));
+    let alternating = [0, 1, 0, 1, 0, 1, 0, 1];
+    println!(
+        "Against an alternating labelling: purity {:.4}, ARI {:.4}",
+        purity(&alternating, &truth),
+        adjusted_rand_index(&alternating, &truth)
+    );
+    
+    println!("\nRunning bisecting k-means with k = 4");
+    let labels = BisectingKMeans::with_seed(4, 100, 42).fit(&data)?;
+    println!("Cluster assignments: {:?}", labels);
+    
+    for (name, labels_and_medoids) in [
+        ("Euclidean", KMedoids::new(3, 100, Euclidean).fit(&data)?),
+        ("Manhattan", KMedoids::new(3, 100, Manhattan).fit(&data)?),
+    ] {
+        let (labels, medoids) = labels_and_medoids;
+        println!("\nRunning k-medoids with k = 3 and {} distance", name);
+        println!("Medoids: {:?}", medoids.iter().map(|&m| data[m].coords.clone()).collect::<Vec<_>>());
+        println!("Cluster assignments: {:?}", labels);
+    }
+    
+    println!("\nRunning k-medoids with k = 2 on one numeric and one categorical coordinate");
+    let mixed_data = vec![
+        Point::new(1.0, 0.0),
+        Point::new(1.5, 0.0),
+        Point::new(1.2, 1.0),
+        Point::new(8.0, 1.0),
+        Point::new(8.5, 1.0),
+        Point::new(9.0, 0.0),
+    ];
+    let mixed = MixedDistance::new(vec![false, true]);
+    println!(
+        "Distances from (1.0, 0): (1.5, 0) = {:.3}, (1.2, 1) = {:.3}, (8.0, 1) = {:.3}",
+        mixed.distance(&mixed_data[0], &mixed_data[1]),
+        mixed.distance(&mixed_data[0], &mixed_data[2]),
+        mixed.distance(&mixed_data[0], &mixed_data[3])
+    );
+    let (labels, _) = KMedoids::new(2, 100, mixed).fit(&mixed_data)?;
+    println!("Cluster assignments: {:?}", labels);
+    
+    for m in [2.0, 10.0] {
+        println!("\nRunning fuzzy c-means with k = 2 and m = {}", m);
+        let (memberships, centroids) = FuzzyCMeans::with_seed(2, 100, m, 42).fit(&data)?;
+        println!("Centroids: {:?}", centroids.iter().map(|c| c.coords.clone()).collect::<Vec<_>>());
+        for (point, row) in data.iter().zip(&memberships) {
+            println!(
+                "{:?}: memberships {:?} (sum {:.3})",
+                point.coords,
+                row.iter().map(|u| format!("{:.3}", u)).collect::<Vec<_>>(),
+                row.iter().sum::<f64>()
+            );
+        }
+    }
+    
+    println!("\nRunning mini-batch k-means with k = 3 and batch_size = 4");
+    let model = MiniBatchKMeans::with_seed(3, 50, 4, 42).fit(&data)?;
+    println!("Inertia (sum of squared distances): {:.4}", model.inertia);
+    println!("Cluster assignments: {:?}", model.labels);
+    
+    println!("\nRunning k-means with k = 3 and tol = 1.0");
+    let mut kmeans = KMeans::with_seed(3, 100, 7);
+    kmeans.tol = 1.0;
+    kmeans.reporter = Box::new(ConsoleReporter);
+    kmeans.fit(&data)?;
+    
+    println!("\nRunning k-means with k = 3 and progress reporting");
+    let mut kmeans = KMeans::with_seed(3, 100, 7);
+    kmeans.reporter = Box::new(ConsoleReporter);
+    kmeans.fit_with_progress(&data, |iteration, inertia| {
+        println!("Iteration {}: inertia {:.4}", iteration, inertia);
+    })?;
+    
+    println!("\nRunning k-means on 3D points with k = 2");
+    let data_3d = vec![
+        Point::from_slice(&[0.0, 0.0, 0.0]),
+        Point::from_slice(&[0.5, 0.2, 0.1]),
+        Point::from_slice(&[0.1, 0.4, 0.3]),
+        Point::from_slice(&[9.0, 9.5, 10.0]),
+        Point::from_slice(&[9.4, 9.1, 9.8]),
+        Point::from_slice(&[10.0, 9.7, 9.2]),
+    ];
+    let mut kmeans = KMeans::with_seed(2, 100, 42);
+    kmeans.n_init = 10;
+    kmeans.reporter = Box::new(ConsoleReporter);
+    let model = kmeans.fit(&data_3d)?;
+    println!("Cluster assignments: {:?}", model.labels);
+    
+    let new_points = vec![
+        Point::from_slice(&[0.3, 0.3, 0.3]),
+        Point::from_slice(&[8.0, 8.0, 8.0]),
+    ];
+    println!("Predicted clusters for new points: {:?}", kmeans.predict(&new_points));
+    println!("Predicted by the returned model: {:?}", model.predict(&new_points));
+    
+    #[cfg(feature = "serde")]
+    {
+        let path = std::env::temp_dir().join("kmeans_model.json");
+        model.save(&path)?;
+        let loaded = KMeansModel::load(&path)?;
+        println!("\nSaved the 3D model to {} and loaded it back", path.display());
+        println!("Loaded model predicts: {:?}", loaded.predict(&new_points));
+    }
+    
+    println!("\nScoring k-means through the Clusterer trait");
+    let mut clusterers: Vec<Box<dyn Clusterer>> = (2..=3.min(data.len()))
+        .map(|k| Box::new(KMeans::with_seed(k, 100, 42)) as Box<dyn Clusterer>)
+        .collect();
+    for clusterer in clusterers.iter_mut() {
+        let (silhouette, db_index) = evaluate(clusterer.as_mut(), &data);
+        println!("Silhouette {:.4}, Davies-Bouldin {:.4}", silhouette, db_index);
+    }
+    
+    if let Err(err) = KMeans::new(data.len() + 1, 100).fit(&data) {
+        println!("\nRejected k = {}: {}", data.len() + 1, err);
+    }
+    
+    let mut with_nan = data.clone();
+    with_nan.push(Point::new(f64::NAN, 1.0));
+    if let Err(err) = KMeans::new(2, 100).fit(&with_nan) {
+        println!("Rejected input: {}", err);
+    }
+    
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> Vec<Point> {
+        vec![
+            Point::new(1.0, 1.0),
+            Point::new(1.0, 8.0),
+            Point::new(2.0, 2.0),
+            Point::new(2.0, 5.0),
+            Point::new(3.0, 1.0),
+            Point::new(4.0, 3.0),
+            Point::new(5.0, 2.0),
+            Point::new(6.0, 1.0),
+            Point::new(6.0, 8.0),
+            Point::new(8.0, 6.0),
+        ]
+    }
+
+    fn with_nan() -> Vec<Point> {
+        vec![
+            Point::new(1.0, 1.0),
+            Point::new(2.0, 1.0),
+            Point::new(f64::NAN, 1.0),
+            Point::new(8.0, 9.0),
+        ]
+    }
+
+    const NAN_AT: KMeansError = KMeansError::NonFinitePoint { index: 2 };
+
+    #[test]
+    fn every_fit_rejects_a_nan_point() {
+        let data = with_nan();
+        assert_eq!(KMeans::new(2, 10).fit(&data).unwrap_err(), NAN_AT);
+        assert_eq!(BisectingKMeans::new(2, 10).fit(&data).unwrap_err(), NAN_AT);
+        assert_eq!(MiniBatchKMeans::new(2, 10, 2).fit(&data).unwrap_err(), NAN_AT);
+        assert_eq!(FuzzyCMeans::new(2, 10, 2.0).fit(&data).unwrap_err(), NAN_AT);
+        assert_eq!(KMedoids::new(2, 10, Euclidean).fit(&data).unwrap_err(), NAN_AT);
+    }
+
+    #[test]
+    fn initial_centroids_are_validated_before_use() {
+        let data = vec![Point::new(0.0, 0.0), Point::new(1.0, 1.0)];
+
+        let mut kmeans = KMeans::new(0, 10);
+        kmeans.set_initial_centroids(Vec::new());
+        assert_eq!(kmeans.fit(&data).unwrap_err(), KMeansError::KIsZero);
+
+        let mut kmeans = KMeans::new(2, 10);
+        kmeans.set_initial_centroids(vec![Point::new(0.0, 0.0)]);
+        assert_eq!(
+            kmeans.fit(&data).unwrap_err(),
+            KMeansError::CentroidCountMismatch { k: 2, found: 1 }
+        );
+
+        let mut kmeans = KMeans::new(2, 10);
+        kmeans.set_initial_centroids(vec![Point::new(0.0, 0.0), Point::from_slice(&[1.0, 1.0, 1.0])]);
+        assert_eq!(
+            kmeans.fit(&data).unwrap_err(),
+            KMeansError::CentroidDimensionMismatch { index: 1, expected: 2, found: 3 }
+        );
+
+        let mut kmeans = KMeans::new(2, 10);
+        kmeans.set_initial_centroids(vec![Point::new(0.0, 0.0), Point::new(1.0, 1.0)]);
+        assert_eq!(kmeans.fit(&data).unwrap().labels, vec![0, 1]);
+    }
+
+    #[test]
+    fn seeded_bisecting_is_reproducible_and_fills_every_cluster() {
+        let data: Vec<Point> = (0..12)
+            .map(|i| Point::new((i % 4) as f64 * 5.0 + (i / 4) as f64 * 0.1, (i / 4) as f64))
+            .collect();
+        let bisecting = BisectingKMeans::with_seed(4, 100, 7);
+        let labels = bisecting.fit(&data).unwrap();
+
+        assert_eq!(bisecting.fit(&data).unwrap(), labels);
+        for label in 0..4 {
+            assert!(labels.contains(&label), "cluster {} is empty in {:?}", label, labels);
+        }
+    }
+
+    #[test]
+    fn bisecting_sse_is_the_spread_around_the_mean() {
+        let points = vec![Point::new(0.0, 0.0), Point::new(2.0, 0.0), Point::new(1.0, 3.0)];
+        // Mean (1, 1): 2 + 2 + 4.
+        assert!((BisectingKMeans::sse(&points) - 8.0).abs() < 1e-12);
+    }
+
+    /// Records every reporter call instead of printing it, behind an `Rc` so
+    /// the test can read the events after handing the box to `KMeans`.
+    #[derive(Default, Clone)]
+    struct Recorder {
+        events: std::rc::Rc<std::cell::RefCell<Vec<String>>>,
+    }
+
+    impl Reporter for Recorder {
+        fn on_tree_built(&mut self, _tree: &dyn fmt::Display) {
+            self.events.borrow_mut().push("tree built".to_string());
+        }
+
+        fn on_converged(&mut self, iterations: usize, _centroids: &[Point]) {
+            self.events.borrow_mut().push(format!("converged after {}", iterations));
+        }
+
+        fn on_depth_limit(&mut self, max_depth: usize) {
+            self.events.borrow_mut().push(format!("depth limit {}", max_depth));
+        }
+    }
+
+    #[test]
+    fn fit_reports_convergence_once_through_the_reporter() {
+        let data = vec![Point::new(0.0, 0.0), Point::new(0.0, 1.0), Point::new(9.0, 9.0), Point::new(9.0, 8.0)];
+        let recorder = Recorder::default();
+        let mut kmeans = KMeans::with_seed(2, 100, 1);
+        kmeans.n_init = 3;
+        kmeans.reporter = Box::new(recorder.clone());
+
+        let model = kmeans.fit(&data).unwrap();
+        assert_eq!(*recorder.events.borrow(), vec![format!("converged after {}", model.iterations)]);
+    }
+
+    /// Runs the ignored test `name` in a child process and returns its
+    /// stdout; the harness gives a test no way to read its own output.
+    fn stdout_of(name: &str) -> String {
+        let output = std::process::Command::new(std::env::current_exe().unwrap())
+            .args([name, "--exact", "--ignored", "--nocapture"])
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+        String::from_utf8(output.stdout).unwrap()
+    }
+
+    #[test]
+    #[ignore = "run in a child process by the_default_reporter_prints_nothing"]
+    fn fit_with_the_default_reporter() {
+        KMeans::new(2, 100).fit(&sample()).unwrap();
+    }
+
+    #[test]
+    #[ignore = "run in a child process by the_default_reporter_prints_nothing"]
+    fn fit_with_the_console_reporter() {
+        let mut kmeans = KMeans::new(2, 100);
+        kmeans.reporter = Box::new(ConsoleReporter);
+        kmeans.fit(&sample()).unwrap();
+    }
+
+    #[test]
+    fn the_default_reporter_prints_nothing() {
+        assert!(stdout_of("tests::fit_with_the_console_reporter").contains("Converged after"));
+
+        let stdout = stdout_of("tests::fit_with_the_default_reporter");
+        assert!(stdout.contains("1 passed"), "{}", stdout);
+        assert!(!stdout.contains("Converged after") && !stdout.contains("Final centroids"), "{}", stdout);
+    }
+
+    #[test]
+    fn gap_statistic_skips_degenerate_k() {
+        let data = vec![Point::new(0.0, 0.0), Point::new(0.5, 0.0), Point::new(9.0, 9.0), Point::new(9.5, 9.0)];
+
+        // k = 4 puts every point on its own centroid: zero inertia.
+        let gaps = gap_statistic(&data, 1..=4, 5, 3);
+        assert_eq!(gaps.iter().map(|&(k, _, _)| k).collect::<Vec<_>>(), vec![1, 2, 3]);
+        assert!(gaps.iter().all(|&(_, gap, se)| gap.is_finite() && se.is_finite()));
+
+        assert!(gap_statistic(&data, 1..=3, 0, 3).is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "point 0")]
+    fn predict_rejects_a_nan_point() {
+        let mut kmeans = KMeans::new(1, 10);
+        kmeans.fit(&[Point::new(1.0, 1.0)]).unwrap();
+        kmeans.predict(&[Point::new(f64::NAN, 1.0)]);
+    }
+
+    #[test]
+    #[should_panic(expected = "point 0")]
+    fn model_predict_rejects_a_nan_point() {
+        let model = KMeans::new(1, 10).fit(&[Point::new(1.0, 1.0)]).unwrap();
+        model.predict(&[Point::new(1.0, f64::NEG_INFINITY)]);
+    }
+
+    #[test]
+    #[should_panic(expected = "point 0")]
+    fn partial_fit_rejects_a_nan_point() {
+        KMeans::new(1, 10).partial_fit(&Point::new(f64::NAN, 1.0));
+    }
+
+    #[test]
+    fn boxed_clusterers_label_every_point() {
+        let data = sample();
+        let mut clusterers: Vec<Box<dyn Clusterer>> =
+            (2..=4).map(|k| Box::new(KMeans::with_seed(k, 100, 42)) as Box<dyn Clusterer>).collect();
+
+        for (clusterer, k) in clusterers.iter_mut().zip(2..) {
+            let labels = clusterer.fit(&data);
+            assert_eq!(labels.len(), data.len());
+            let mut clusters: Vec<usize> = labels.into_iter().map(|label| label.expect("k-means labels every point")).collect();
+            clusters.sort();
+            clusters.dedup();
+            assert_eq!(clusters, (0..k).collect::<Vec<_>>());
+        }
+    }
+
+    #[test]
+    fn the_same_seed_gives_bit_identical_centroids() {
+        let data = sample();
+        let bits = |model: &KMeansModel| -> Vec<u64> {
+            model.centroids.iter().flat_map(|c| c.coords.iter().map(|x| x.to_bits())).collect()
+        };
+
+        let first = KMeans::with_seed(3, 100, 42).fit(&data).unwrap();
+        let second = KMeans::with_seed(3, 100, 42).fit(&data).unwrap();
+        assert_eq!(bits(&first), bits(&second));
+        assert_eq!(first.labels, second.labels);
+    }
+
+    #[test]
+    fn more_restarts_never_raise_the_inertia() {
+        let data = sample();
+        for seed in 0..10 {
+            let single = KMeans::with_seed(3, 100, seed).fit(&data).unwrap();
+            let mut kmeans = KMeans::with_seed(3, 100, seed);
+            kmeans.n_init = 10;
+            let restarted = kmeans.fit(&data).unwrap();
+
+            assert!(restarted.inertia <= single.inertia, "seed {}", seed);
+            // The kept labels and centroids belong to the best run.
+            assert_eq!(kmeans.inertia(&data, &restarted.labels), restarted.inertia);
+        }
+    }
+
+    #[test]
+    fn degenerate_inputs_return_their_error() {
+        let data = sample();
+
+        assert_eq!(KMeans::new(2, 10).fit(&[]).unwrap_err(), KMeansError::EmptyData);
+        assert_eq!(KMeans::new(0, 10).fit(&data).unwrap_err(), KMeansError::KIsZero);
+        assert_eq!(KMeans::new(11, 10).fit(&data).unwrap_err(), KMeansError::KTooLarge { k: 11, n: 10 });
+        assert!(KMeans::new(10, 10).fit(&data).is_ok());
+    }
+
+    fn blobs_3d() -> Vec<Point> {
+        vec![
+            Point::from_slice(&[0.0, 0.0, 0.0]),
+            Point::from_slice(&[0.5, 0.2, 0.1]),
+            Point::from_slice(&[0.1, 0.4, 0.3]),
+            Point::from_slice(&[9.0, 9.5, 10.0]),
+            Point::from_slice(&[9.4, 9.1, 9.8]),
+            Point::from_slice(&[10.0, 9.7, 9.2]),
+        ]
+    }
+
+    #[test]
+    fn three_dimensional_points_split_into_their_two_groups() {
+        let mut kmeans = KMeans::with_seed(2, 100, 42);
+        kmeans.n_init = 10;
+        let model = kmeans.fit(&blobs_3d()).unwrap();
+
+        let labels = &model.labels;
+        assert!(labels[..3].iter().all(|&label| label == labels[0]));
+        assert!(labels[3..].iter().all(|&label| label == labels[3]));
+        assert_ne!(labels[0], labels[3]);
+        assert!(model.centroids.iter().all(|centroid| centroid.dim() == 3));
+    }
+
+    #[test]
+    #[should_panic(expected = "dimension mismatch")]
+    fn predicting_a_point_of_another_dimension_panics() {
+        let mut kmeans = KMeans::with_seed(2, 100, 42);
+        kmeans.fit(&blobs_3d()).unwrap();
+        kmeans.predict(&[Point::new(1.0, 1.0)]);
+    }
+
+    #[test]
+    fn a_larger_tolerance_stops_sooner() {
+        let data = sample();
+        let iterations = |seed, tol| {
+            let mut kmeans = KMeans::with_seed(3, 100, seed);
+            kmeans.tol = tol;
+            kmeans.fit(&data).unwrap().iterations
+        };
+
+        for seed in 0..10 {
+            assert!(iterations(seed, 1.0) <= iterations(seed, 1e-6), "seed {}", seed);
+        }
+        assert!(iterations(7, 1.0) < iterations(7, 1e-6));
+    }
+
+    #[test]
+    fn predict_reproduces_the_training_labels() {
+        let data = sample();
+        let mut kmeans = KMeans::with_seed(3, 100, 42);
+        kmeans.n_init = 5;
+        let model = kmeans.fit(&data).unwrap();
+
+        assert_eq!(kmeans.predict(&data), model.labels);
+        assert_eq!(model.predict(&data), model.labels);
+        assert_eq!(kmeans.inertia(&data, &model.labels), model.inertia);
+    }
+
+    #[test]
+    fn an_empty_cluster_steals_a_point_and_stays_active() {
+        let data: Vec<Point> = [0.0, 1.0, 10.0, 11.0, 12.0].iter().map(|&x| Point::new(x, 0.0)).collect();
+        let mut kmeans = KMeans::new(3, 100);
+        // Every point is nearer (100, 0) than (101, 0), so naive updates
+        // would leave the third centroid without points for good.
+        kmeans.set_initial_centroids(vec![Point::new(0.5, 0.0), Point::new(100.0, 0.0), Point::new(101.0, 0.0)]);
+
+        let model = kmeans.fit(&data).unwrap();
+        for cluster in 0..3 {
+            assert!(model.labels.contains(&cluster), "cluster {} is empty in {:?}", cluster, model.labels);
+        }
+        assert!(model.centroids.iter().all(|centroid| centroid.x() < 13.0));
+    }
+
+    #[test]
+    fn medoid_cost_never_increases_with_more_swaps() {
+        let data = sample();
+        let distances = distance_matrix(&data, &Euclidean);
+
+        // Capping the swap phase at `i` iterations replays the cost after each.
+        let costs: Vec<f64> = (0..6)
+            .map(|max_iterations| {
+                let (labels, medoids) = KMedoids::new(3, max_iterations, Euclidean).fit(&data).unwrap();
+                let assigned: f64 = labels.iter().enumerate().map(|(i, &label)| distances[i][medoids[label]]).sum();
+                let cost = KMedoids::<Euclidean>::cost(&distances, &medoids);
+                assert!((assigned - cost).abs() < 1e-9);
+                cost
+            })
+            .collect();
+        assert!(costs.windows(2).all(|w| w[1] <= w[0]), "{:?}", costs);
+    }
+
+    #[test]
+    fn mini_batch_inertia_is_close_to_full_k_means() {
+        let data = sample();
+        let mut kmeans = KMeans::with_seed(3, 100, 42);
+        kmeans.n_init = 10;
+        let full = kmeans.fit(&data).unwrap();
+
+        for seed in 0..5 {
+            let mini_batch = MiniBatchKMeans::with_seed(3, 50, 4, seed).fit(&data).unwrap();
+            assert_eq!(mini_batch.labels.len(), data.len());
+            assert!(mini_batch.inertia <= 2.0 * full.inertia, "seed {}: {} vs {}", seed, mini_batch.inertia, full.inertia);
+        }
+    }
+
+    fn two_blobs() -> Vec<Point> {
+        vec![
+            Point::new(0.0, 0.0),
+            Point::new(0.5, 0.3),
+            Point::new(0.2, 0.8),
+            Point::new(0.7, 0.6),
+            Point::new(10.0, 10.0),
+            Point::new(10.4, 9.6),
+            Point::new(9.7, 10.5),
+            Point::new(10.2, 10.3),
+        ]
+    }
+
+    #[test]
+    fn the_elbow_of_two_blobs_is_at_two() {
+        let curve = elbow(&two_blobs(), 1..=6, 100);
+
+        assert_eq!(curve.iter().map(|&(k, _)| k).collect::<Vec<_>>(), (1..=6).collect::<Vec<_>>());
+        assert_eq!(suggest_k(&curve), 2, "{:?}", curve);
+        assert_eq!(suggest_k(&[]), 0);
+    }
+
+    #[test]
+    fn reported_inertia_never_increases() {
+        let data = sample();
+        for seed in 0..5 {
+            let mut reported = Vec::new();
+            let model = KMeans::with_seed(3, 100, seed)
+                .fit_with_progress(&data, |iteration, inertia| reported.push((iteration, inertia)))
+                .unwrap();
+
+            assert_eq!(reported.len(), model.iterations);
+            assert!(reported.iter().enumerate().all(|(i, &(iteration, _))| iteration == i + 1));
+            assert!(reported.windows(2).all(|w| w[1].1 <= w[0].1 + 1e-9), "seed {}: {:?}", seed, reported);
+        }
+    }
+
+    #[test]
+    fn memberships_sum_to_one_and_flatten_as_m_grows() {
+        let data = sample();
+        // Mean distance of a membership row from uniform.
+        let spread = |m: f64| {
+            let (memberships, _) = FuzzyCMeans::with_seed(2, 100, m, 42).fit(&data).unwrap();
+            for row in &memberships {
+                assert!((row.iter().sum::<f64>() - 1.0).abs() < 1e-9, "m = {}: {:?}", m, row);
+            }
+            memberships.iter().map(|row| (row[0] - 0.5).abs()).sum::<f64>() / data.len() as f64
+        };
+
+        let (sharp, flat) = (spread(2.0), spread(10.0));
+        assert!(flat < sharp, "m = 10 spread {} vs m = 2 spread {}", flat, sharp);
+        assert!(flat < 0.1);
+    }
+
+    #[test]
+    fn the_gap_of_two_blobs_peaks_at_two() {
+        let gaps = gap_statistic(&two_blobs(), 1..=4, 10, 42);
+
+        assert_eq!(gaps.iter().map(|&(k, _, _)| k).collect::<Vec<_>>(), vec![1, 2, 3, 4]);
+        let best = gaps.iter().max_by(|a, b| a.1.total_cmp(&b.1)).unwrap();
+        assert_eq!(best.0, 2, "{:?}", gaps);
+    }
+
+    #[test]
+    fn farthest_first_picks_the_same_distinct_data_points_every_time() {
+        let data = sample();
+        let mut seeded = Vec::new();
+        for seed in [1, 2] {
+            let mut kmeans = KMeans::with_seed(3, 100, seed);
+            kmeans.init = InitMethod::FarthestFirst;
+            kmeans.initialize_centroids(&data, 0).unwrap();
+            seeded.push(kmeans.centroids);
+        }
+
+        assert_eq!(seeded[0], seeded[1]);
+        let centroids = &seeded[0];
+        assert_eq!(centroids.len(), 3);
+        for (i, centroid) in centroids.iter().enumerate() {
+            assert!(data.contains(centroid), "{:?} is not a data point", centroid.coords);
+            assert!(!centroids[..i].contains(centroid), "{:?} was picked twice", centroid.coords);
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn a_saved_model_loads_back_unchanged() {
+        let data = blobs_3d();
+        let model = KMeans::with_seed(2, 100, 42).fit(&data).unwrap();
+        let path = std::env::temp_dir().join(format!("kmeans_round_trip_{}.json", std::process::id()));
+
+        model.save(&path).unwrap();
+        let loaded = KMeansModel::load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(loaded.centroids, model.centroids);
+        assert_eq!(loaded.labels, model.labels);
+        assert_eq!(loaded.inertia.to_bits(), model.inertia.to_bits());
+        assert_eq!(loaded.predict(&data), model.predict(&data));
+    }
+
+    #[test]
+    fn elkan_assigns_the_same_labels_as_lloyd() {
+        let data = sample();
+        for seed in 0..5 {
+            let mut labels = Vec::new();
+            for algorithm in [Algorithm::Lloyd, Algorithm::Elkan] {
+                let mut kmeans = KMeans::with_seed(3, 100, seed);
+                kmeans.algorithm = algorithm;
+                kmeans.initialize_centroids(&data, 0).unwrap();
+                labels.push(kmeans.lloyd(&data, &mut |_, _| {}, None).0);
+            }
+            assert_eq!(labels[0], labels[1], "seed {}", seed);
+        }
+    }
+
+    #[test]
+    fn consensus_keeps_blobs_together_and_apart() {
+        let matrix = consensus(&two_blobs(), 2, 20, 7).unwrap();
+
+        for (i, row) in matrix.iter().enumerate() {
+            assert_eq!(row[i], 1.0);
+            for (j, &value) in row.iter().enumerate() {
+                assert_eq!(value, matrix[j][i]);
+                if (i < 4) == (j < 4) {
+                    assert!(value >= 0.8, "points {} and {} together in only {}", i, j, value);
+                } else {
+                    assert!(value <= 0.2, "points {} and {} together in {}", i, j, value);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn starting_at_the_true_centers_converges_immediately() {
+        let data = two_blobs();
+        let centers = vec![Point::new(0.35, 0.425), Point::new(10.075, 10.1)];
+        let mut kmeans = KMeans::new(2, 100);
+        kmeans.set_initial_centroids(centers.clone());
+
+        let model = kmeans.fit(&data).unwrap();
+
+        assert!(model.iterations <= 1, "took {} iterations", model.iterations);
+        assert_eq!(model.labels, vec![0, 0, 0, 0, 1, 1, 1, 1]);
+        for (fitted, center) in model.centroids.iter().zip(&centers) {
+            assert!(fitted.distance(center) < 1e-9);
+        }
+    }
+
+    #[test]
+    fn the_trajectory_runs_from_the_initial_to_the_fitted_centroids() {
+        let data = sample();
+        let mut initial = KMeans::with_seed(3, 100, 42);
+        initial.initialize_centroids(&data, 0).unwrap();
+
+        let mut kmeans = KMeans::with_seed(3, 100, 42);
+        let (labels, trajectory) = kmeans.fit_tracked(&data).unwrap();
+
+        assert!(trajectory.len() >= 2);
+        assert_eq!(trajectory.first(), Some(&initial.centroids));
+        assert_eq!(trajectory.last(), Some(&kmeans.centroids));
+        assert_eq!(labels, kmeans.predict(&data));
+    }
+
+    #[test]
+    fn partial_fit_pulls_only_the_nearest_centroid_toward_a_burst() {
+        let data = sample();
+        let mut kmeans = KMeans::with_seed(2, 100, 42);
+        let model = kmeans.fit(&data).unwrap();
+        let burst = [Point::new(9.0, 9.0), Point::new(9.2, 8.8), Point::new(8.8, 9.1), Point::new(9.1, 9.2)];
+        let target = nearest_centroids(&model.centroids, &burst[..1])[0];
+        let absorbed = model.labels.iter().filter(|&&label| label == target).count();
+
+        for point in &burst {
+            kmeans.partial_fit(point);
+        }
+
+        let moved = &kmeans.centroids[target];
+        assert!(moved.distance(&burst[0]) < model.centroids[target].distance(&burst[0]));
+        assert_eq!(kmeans.centroids[1 - target], model.centroids[1 - target]);
+        let total = burst.iter().fold(model.centroids[target].scale(absorbed as f64), |sum, p| sum.add(p));
+        let mean = total.scale(1.0 / (absorbed + burst.len()) as f64);
+        assert!(moved.distance(&mean) < 1e-9, "{:?} vs {:?}", moved.coords, mean.coords);
+    }
+
+    #[test]
+    fn fit_predict_inertia_matches_its_labels_and_centroids() {
+        let data = sample();
+        let (labels, centroids, inertia) = KMeans::fit_predict(&data, 2, 100, 42).unwrap();
+        let mut kmeans = KMeans::with_seed(2, 100, 42);
+        kmeans.centroids = centroids;
+
+        assert_eq!(labels.len(), data.len());
+        assert_eq!(kmeans.predict(&data), labels);
+        assert!((kmeans.inertia(&data, &labels) - inertia).abs() < 1e-9);
+    }
 }
@@ -53,7 +53,7 @@ impl KMedians {
                 
                 for (i, centroid) in self.centroids.iter().enumerate() {
                     let dist = point.manhattan_distance(centroid);
-                    if dist < min_dist {
+                    if dist.total_cmp(&min_dist).is_lt() {
                         min_dist = dist;
                         cluster = i;
                     }
@@ -70,7 +70,7 @@ impl KMedians {
         }
         
         let mut sorted_values = values;
-        sorted_values.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        sorted_values.sort_by(f64::total_cmp);
         
         let mid = sorted_values.len() / 2;
         if sorted_values.len() % 2 == 0 {